@@ -28,7 +28,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 cfg.minify_css = true;
                 cfg.keep_closing_tags = true;
                 cfg.keep_html_and_head_opening_tags = true;
-                let minified = minify(&file_content.as_bytes(), &cfg);
+                let minified = minify(file_content.as_bytes(), &cfg);
                 String::from_utf8(minified)?
             } else {
                 file_content
@@ -41,13 +41,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                 format!( "{start_marker}\r\n{file_content}\r\n{end_marker}")
             };
 
-            if let Some(start) = content.find(&start_marker) {
-                if let Some(end) = content.find(&end_marker) {
-                    content.replace_range(
-                        start..end + end_marker.len(),
-                        &replace_value.as_str(),
-                    )
-                }
+            if let Some(start) = content.find(&start_marker)
+                && let Some(end) = content.find(&end_marker)
+            {
+                content.replace_range(start..end + end_marker.len(), replace_value.as_str())
             }
         }
         readme.write_all_at(content.as_bytes(), 0)?;
@@ -0,0 +1,8 @@
+Feature: odd file name
+    The page of this feature is named after a file with spaces and quotes
+
+    Scenario: A page with a safe name
+        Given a fact
+
+    Scenario: A step too long for one line of the pdf
+        Given a fact with 'a value that is long enough to be wrapped over more than one line, with “typographic” quotes, a € sign and some Привет'
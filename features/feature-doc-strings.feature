@@ -0,0 +1,19 @@
+Feature: doc strings
+
+    Scenario: A step with a json doc string
+        Given a request body
+            """json
+            {
+                "name": "cucumber",
+                "tags": ["bdd", "testing"]
+            }
+            """
+        When something is executed
+        Then the result is oke
+
+    Scenario: A step with a plain doc string
+        Given a request body
+            """
+            Some text
+              that keeps its indentation
+            """
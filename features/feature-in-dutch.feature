@@ -0,0 +1,18 @@
+# language: nl
+Functionaliteit: in het Nederlands
+    Met Nederlandse sleutelwoorden
+
+    Scenario: Een scenario
+        Gegeven a fact
+        En a other fact
+        Als something is executed
+        Dan the result is oke
+
+    Abstract Scenario: Een abstract scenario met '<value>'
+        Gegeven a fact with '<value>'
+        Maar a other fact
+
+        Voorbeelden:
+            | value   |
+            | Value 1 |
+            | Value 3 |
@@ -0,0 +1,11 @@
+Feature: markdown description
+    Acceptance criteria written in **markdown**:
+
+    * the description keeps its *line breaks*
+    * lists are rendered as lists
+    * scripts are removed <script>alert("hostile")</script>
+
+    Scenario: Scenario with a markdown description
+        A [link](https://cucumber.io) in the description
+        Given a fact
+        Then the result is oke
@@ -0,0 +1,22 @@
+Feature: outline templates
+    The example values of these outlines do not tell their
+    placeholders apart, they are reported with the values of the first row
+
+    Scenario Outline: Single row outline
+        Given a fact with '<value>'
+
+        Examples:
+            | value   |
+            | Value 1 |
+
+    Scenario Outline: Outline with identical columns
+        The columns have the same values,
+        the steps use them in another order
+
+        Given a fact with '<second>'
+        And a fact with '<first>'
+
+        Examples:
+            | first   | second  |
+            | Value 1 | Value 1 |
+            | Value 3 | Value 3 |
@@ -0,0 +1,19 @@
+Feature: outline with comments
+
+    Scenario Outline: Outline with formatted examples '<value>'
+        Given a fact with '<value>'
+
+        Examples: with comments and blank lines
+            # the values to check
+            | value   |
+
+            # a passing one
+            | Value 1 |
+            # a failing one
+
+            | Value 2 |
+            | Value 1 |
+
+        Examples: directly after the first ones
+            | value   |
+            | Value 3 |
@@ -0,0 +1,23 @@
+Feature: step states
+
+    Scenario: Scenario with an undefined step
+        Given a fact
+        And a step without a definition
+        Then the result is oke
+
+    Scenario: Scenario with an ambiguous step
+        Given an ambiguous fact
+        Then the result is oke
+
+    Scenario: Scenario with a failed step
+        Given a fact
+        Then the result is failed
+        And the result is oke
+
+    Scenario: Scenario failing with colored output
+        Given a fact
+        Then the output is colored
+
+    Scenario: Scenario failing with a code block
+        Given a fact
+        Then the output has a code block
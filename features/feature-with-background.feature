@@ -0,0 +1,25 @@
+Feature: background
+    The background runs before every scenario
+
+    Background: common facts
+        Given a fact
+
+    Scenario: Scenario after the background
+        Given a other fact
+        Then the result is oke
+
+    Scenario Outline: Outline after the background
+        Given a fact with '<value>'
+
+        Examples:
+            | value   |
+            | Value 1 |
+
+    Rule: a rule with a failing background
+
+        Background:
+            Given a fact with 'Value 2'
+
+        Scenario: Scenario not run due to the background
+            Given a fact
+            Then the result is oke
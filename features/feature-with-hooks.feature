@@ -0,0 +1,25 @@
+Feature: hooks
+    The before and after hooks run for every scenario
+
+    Scenario: Scenario with passing hooks
+        Given a fact
+
+    @failing_before_hook
+    Scenario: Scenario with a failing before hook
+        Given a fact
+
+    @failing_after_hook
+    Scenario: Scenario with a failing after hook
+        Given a fact
+
+    Scenario Outline: Outline with a failing before hook for one of the same rows
+        Given a fact with '<value>'
+
+        Examples: without a failing hook
+            | value   |
+            | Value 1 |
+
+        @failing_before_hook
+        Examples: with a failing before hook
+            | value   |
+            | Value 1 |
@@ -0,0 +1,18 @@
+Feature: retries
+
+    Background:
+        Given a fact
+
+    @retry(2)
+    Scenario: Scenario passing after a retry
+        Given a fact
+        And a flaky fact
+
+    @retry(1)
+    Scenario: Scenario failing every attempt
+        Given a fact
+        Then the result is failed
+
+    @retry(2)
+    Scenario: Scenario passing the first time
+        Given a fact
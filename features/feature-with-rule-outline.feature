@@ -0,0 +1,11 @@
+Feature: outline in a rule
+
+    Rule: a rule with an outline
+
+        Scenario Outline: Outline in a rule with '<value>'
+            Given a fact with '<value>'
+
+            Examples:
+                | value   |
+                | Value 1 |
+                | Value 3 |
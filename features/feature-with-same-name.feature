@@ -0,0 +1,5 @@
+Feature: tags
+    Same name as the feature in feature-with-tags.feature, both get their own page
+
+    Scenario: Scenario in a feature with a duplicate name
+        Given a fact
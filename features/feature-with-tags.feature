@@ -0,0 +1,31 @@
+@tagged
+Feature: tags
+
+    @smoke
+    Scenario: A smoke test
+        Given a fact
+        Then the result is oke
+
+    @regression @team-a
+    Scenario: A regression test
+        Given a fact
+        Then the result is failed
+
+    Scenario Outline: An outline with tagged examples
+        Given a fact with '<value>'
+
+        @smoke
+        Examples:
+            | value   |
+            | Value 1 |
+
+        @regression
+        Examples:
+            | value   |
+            | Value 2 |
+
+    @team-b
+    Rule: a rule with tags
+
+        Scenario: Scenario inheriting the rule tags
+            Given a fact
@@ -2,4 +2,4 @@
 
 mod render_types;
 mod reporter;
-pub use reporter::CucumberReporter;
+pub use reporter::{CucumberReporter, CucumberReporterBuilder, FilenameStrategy, ReportFormat};
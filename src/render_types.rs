@@ -1,12 +1,21 @@
+use cucumber::event::{HookType, Info, StepError};
 use gherkin::Step;
 use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct FeatureRenderData {
     pub name: String,
     pub description: String,
+    pub tags: Vec<String>,
+    /// Tags of everything in the feature, to filter on
+    pub all_tags: Vec<String>,
+    pub background: Option<BackgroundRenderData>,
     pub scenarios: String,
     pub rules: String,
+    pub duration: Option<DurationRenderData>,
+    /// Set when the feature could not be reported
+    pub error: Option<String>,
 }
 
 #[derive(Serialize, Clone, Debug,Default)]
@@ -14,47 +23,492 @@ pub(crate) struct FeatureRenderStatsData {
     pub name: String,
     pub link: String,
     pub description:String,
+    pub tags: Vec<String>,
+    /// Tags of everything in the feature, to filter on
+    pub all_tags: Vec<String>,
     pub nr_scenarios: usize,
     pub nr_rules: usize,
     pub nr_steps: usize,
     pub nr_errors: usize,
+    pub nr_undefined: usize,
+    pub nr_ambiguous: usize,
+    /// Failed background steps, counted once for every scenario they ran for
+    pub nr_background_errors: usize,
+    /// Scenarios that passed after a retry
+    pub nr_flaky: usize,
     pub nr_skipped: usize,
+    pub nr_not_run: usize,
+    /// Set when the feature could not be reported
+    pub error: Option<String>,
+    pub duration: Option<DurationRenderData>,
+}
+
+/// The structured results of a run, the html pages are rendered from it
+/// and it is written as is for the json report
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct ReportData {
+    /// Failed when a feature file could not be parsed or a scenario failed
+    pub status: StepState,
+    pub features: Vec<FeatureReportData>,
+    pub parse_errors: Vec<ParseErrorRenderData>,
+    pub tags: Vec<TagStatsRenderData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+/// A feature file that could not be parsed, it has no results
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct ParseErrorRenderData {
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct FeatureReportData {
+    #[serde(flatten)]
+    pub stats: FeatureRenderStatsData,
+    pub background: Option<BackgroundRenderData>,
+    pub rules: Vec<RuleReportData>,
+    pub scenarios: Vec<ScenarioReportData>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct RuleReportData {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub background: Option<BackgroundRenderData>,
+    pub scenarios: Vec<ScenarioReportData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub(crate) enum ScenarioReportData {
+    Scenario(ScenarioRenderData),
+    Outline(OutlineRenderData),
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct PageRenderData {
+    pub title: String,
+    pub content: String,
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct IndexRenderData {
-    pub features : Vec<FeatureRenderStatsData>
+    pub title: String,
+    pub status: StepState,
+    pub features : Vec<FeatureRenderStatsData>,
+    pub parse_errors: Vec<ParseErrorRenderData>,
+    pub tags: Vec<TagStatsRenderData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+/// The index and every feature page in one html file
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SingleRenderData {
+    pub title: String,
+    pub index: IndexRenderData,
+    pub features: Vec<SingleFeatureRenderData>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SingleFeatureRenderData {
+    /// Id of the section, the links of the index point to it
+    pub anchor: String,
+    pub content: String,
+}
+
+/// Outcome of the scenarios with a tag, including the tags inherited
+/// from their feature, rule and examples
+#[derive(Serialize, Clone, Debug, Default)]
+pub(crate) struct TagStatsRenderData {
+    pub name: String,
+    pub nr_scenarios: usize,
+    pub nr_passed: usize,
+    pub nr_failed: usize,
+    pub nr_undefined: usize,
+    pub nr_skipped: usize,
+}
+
+/// Elapsed time between the started and finished event of a step, scenario, rule, feature or run
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct DurationRenderData {
+    pub millis: u64,
+    pub display: String,
+    /// Milliseconds since the unix epoch
+    pub start: Option<u64>,
+}
+
+impl From<&Duration> for DurationRenderData {
+    fn from(duration: &Duration) -> Self {
+        let millis = duration.as_millis() as u64;
+        let display = match millis {
+            0..1_000 => format!("{millis} ms"),
+            1_000..60_000 => format!("{:.2} s", duration.as_secs_f64()),
+            _ => format!("{}m {}s", millis / 60_000, (millis % 60_000) / 1_000),
+        };
+        Self {
+            millis,
+            display,
+            start: None,
+        }
+    }
+}
+
+impl DurationRenderData {
+    pub(crate) fn with_start(mut self, start: Option<SystemTime>) -> Self {
+        self.start = start
+            .and_then(|start| start.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_millis() as u64);
+        self
+    }
+
+    /// Milliseconds since the unix epoch
+    pub(crate) fn stop(&self) -> Option<u64> {
+        self.start.map(|start| start + self.millis)
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct StepRenderData {
+    /// Given, When or Then, also for an And or But and in any language
     pub step_type: String,
+    /// The keyword as written in the feature
+    pub keyword: String,
+    /// An And or But step, it is indented
+    pub continuation: bool,
     pub step_state: StepState,
     pub step_template: String,
-    pub step_table: Option<Vec<Vec<String>>>
+    pub step_table: Option<Vec<Vec<String>>>,
+    pub step_docstring: Option<DocStringRenderData>,
+    pub step_error: Option<StepErrorRenderData>,
+    pub duration: Option<DurationRenderData>,
 }
 
-/// Todo: gerkin languages
 impl StepRenderData {
     pub(crate) fn new(step: &Step, state: StepState) -> Self {
         Self {
-            step_type: step.keyword.trim().to_string(),
+            step_type: format!("{:?}", step.ty),
+            keyword: step.keyword.trim().to_string(),
+            continuation: false,
             step_template: step.value.clone(),
             step_state: state,
-            step_table: step.table.as_ref().map(|t| t.rows.clone())
+            step_table: step.table.as_ref().map(|t| t.rows.clone()),
+            step_docstring: step.docstring.as_deref().map(DocStringRenderData::from),
+            step_error: None,
+            duration: None,
+        }
+    }
+
+    /// A before or after hook, shown as a step
+    pub(crate) fn hook(hook_type: HookType, state: StepState) -> Self {
+        Self {
+            step_type: hook_type.to_string(),
+            keyword: hook_type.to_string(),
+            continuation: false,
+            step_template: "hook".to_string(),
+            step_state: state,
+            step_table: None,
+            step_docstring: None,
+            step_error: None,
+            duration: None,
+        }
+    }
+
+    pub(crate) fn with_error(mut self, error: Option<StepErrorRenderData>) -> Self {
+        self.step_error = error;
+        self
+    }
+
+    pub(crate) fn with_duration(mut self, duration: Option<DurationRenderData>) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+/// Marks the And and But steps of a scenario or background, without knowing
+/// those keywords in the language of the feature: a step continues when it
+/// has the type of the step before it, but not the keyword that started that
+/// type. A repeated `Given` starts again.
+pub(crate) fn grouped(steps: impl IntoIterator<Item = StepRenderData>) -> Vec<StepRenderData> {
+    let mut steps = steps.into_iter().collect::<Vec<_>>();
+    let mut started_by = 0;
+    for id in 1..steps.len() {
+        steps[id].continuation = steps[id].step_type == steps[id - 1].step_type
+            && steps[id].keyword != steps[started_by].keyword;
+        if !steps[id].continuation {
+            started_by = id;
+        }
+    }
+    steps
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct DocStringRenderData {
+    /// The optional content type after the opening delimiter, e.g. `json`
+    pub content_type: Option<String>,
+    pub content: String,
+}
+
+/// The parser keeps the content type as the first line and only removes the
+/// indentation when there is no content type, so both are done here.
+impl From<&str> for DocStringRenderData {
+    fn from(docstring: &str) -> Self {
+        let (first, rest) = docstring.split_once('\n').unwrap_or((docstring, ""));
+        let content_type = Some(first.trim()).filter(|t| !t.is_empty());
+        let lines = rest.trim_end().lines().collect::<Vec<_>>();
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or_default();
+        Self {
+            content_type: content_type.map(str::to_string),
+            content: lines
+                .iter()
+                .map(|line| line.get(indent..).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+/// Why a step failed, as reported by cucumber
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct StepErrorRenderData {
+    pub kind: StepErrorKind,
+    pub message: String,
+    /// Only present when the step panicked
+    pub payload: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub(crate) enum StepErrorKind {
+    Panic,
+    NotFound,
+    AmbiguousMatch,
+}
+
+impl From<&StepError> for StepErrorRenderData {
+    fn from(error: &StepError) -> Self {
+        let (kind, payload) = match error {
+            StepError::NotFound => (StepErrorKind::NotFound, None),
+            StepError::AmbiguousMatch(_) => (StepErrorKind::AmbiguousMatch, None),
+            StepError::Panic(info) => (StepErrorKind::Panic, Some(panic_payload(info))),
+        };
+        Self {
+            kind,
+            message: error.to_string(),
+            payload,
+        }
+    }
+}
+
+/// A failed hook, hooks can only fail by panicking
+impl From<&Info> for StepErrorRenderData {
+    fn from(info: &Info) -> Self {
+        let payload = panic_payload(info);
+        Self {
+            kind: StepErrorKind::Panic,
+            message: format!("Hook panicked. Captured output: {payload}"),
+            payload: Some(payload),
         }
     }
 }
 
+/// Panics carry either a `String` or a `&str`, anything else can't be shown
+pub(crate) fn panic_payload(info: &Info) -> String {
+    info.downcast_ref::<String>()
+        .cloned()
+        .or_else(|| info.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "(Could not resolve panic payload)".to_string())
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct RuleRenderData {
     pub name: String,
     pub description: String,
+    pub tags: Vec<String>,
+    pub background: Option<BackgroundRenderData>,
     pub scenarios: String,
+    pub duration: Option<DurationRenderData>,
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct ScenarioRenderData {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    /// Own and inherited tags, to filter on
+    pub all_tags: Vec<String>,
+    /// The before hook as a pseudo step, when it ran
+    pub before_hooks: Vec<StepRenderData>,
+    /// The background steps as they ran for this scenario
+    pub background: Vec<StepRenderData>,
+    pub steps: Vec<StepRenderData>,
+    /// The after hook as a pseudo step, when it ran
+    pub after_hooks: Vec<StepRenderData>,
+    /// Passed after a retry
+    pub flaky: bool,
+    /// All attempts, only when the scenario was retried
+    pub attempts: Vec<AttemptRenderData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+impl ScenarioRenderData {
+    /// Everything that ran for the scenario in order: the before hook,
+    /// the background steps, the steps and the after hook
+    pub(crate) fn all_steps(&self) -> Vec<StepRenderData> {
+        all_steps(&self.before_hooks, &self.background, &self.steps, &self.after_hooks)
+    }
+
+    pub(crate) fn state(&self) -> StepState {
+        let states = self
+            .all_steps()
+            .into_iter()
+            .map(|step| step.step_state)
+            .collect::<Vec<_>>();
+        StepState::combined(&states)
+    }
+}
+
+fn all_steps(
+    before_hooks: &[StepRenderData],
+    background: &[StepRenderData],
+    steps: &[StepRenderData],
+    after_hooks: &[StepRenderData],
+) -> Vec<StepRenderData> {
+    before_hooks
+        .iter()
+        .chain(background)
+        .chain(steps)
+        .chain(after_hooks)
+        .cloned()
+        .collect()
+}
+
+/// A scenario or an example row of an outline as it ran, for the reports
+/// that have a test for every run
+pub(crate) struct ScenarioRun<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    pub tags: &'a [String],
+    /// Headers and values of the example row
+    pub parameters: Vec<(String, String)>,
+    pub before_hooks: &'a [StepRenderData],
+    pub background: &'a [StepRenderData],
+    pub steps: &'a [StepRenderData],
+    pub after_hooks: &'a [StepRenderData],
+    pub flaky: bool,
+    pub attempts: &'a [AttemptRenderData],
+    pub duration: Option<&'a DurationRenderData>,
+}
+
+impl ScenarioRun<'_> {
+    /// The name with the values of the example row, the rows of an outline share its name
+    pub(crate) fn display_name(&self) -> String {
+        if self.parameters.is_empty() {
+            self.name.to_string()
+        } else {
+            let values = self
+                .parameters
+                .iter()
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>();
+            format!("{} ({})", self.name, values.join(", "))
+        }
+    }
+
+    pub(crate) fn all_steps(&self) -> Vec<StepRenderData> {
+        all_steps(self.before_hooks, self.background, self.steps, self.after_hooks)
+    }
+
+    pub(crate) fn state(&self) -> StepState {
+        let states = self
+            .all_steps()
+            .into_iter()
+            .map(|step| step.step_state)
+            .collect::<Vec<_>>();
+        StepState::combined(&states)
+    }
+}
+
+impl ScenarioReportData {
+    pub(crate) fn runs(&self) -> Vec<ScenarioRun<'_>> {
+        match self {
+            ScenarioReportData::Scenario(scenario) => vec![ScenarioRun {
+                name: &scenario.name,
+                description: &scenario.description,
+                tags: &scenario.all_tags,
+                parameters: Vec::new(),
+                before_hooks: &scenario.before_hooks,
+                background: &scenario.background,
+                steps: &scenario.steps,
+                after_hooks: &scenario.after_hooks,
+                flaky: scenario.flaky,
+                attempts: &scenario.attempts,
+                duration: scenario.duration.as_ref(),
+            }],
+            ScenarioReportData::Outline(outline) => outline
+                .examples
+                .iter()
+                .flat_map(|example| {
+                    example.rows.iter().map(|row| ScenarioRun {
+                        name: &outline.name,
+                        description: &outline.scenario_description,
+                        tags: &outline.all_tags,
+                        parameters: example
+                            .headers
+                            .iter()
+                            .cloned()
+                            .zip(row.example.iter().cloned())
+                            .collect(),
+                        before_hooks: &row.before_hooks,
+                        background: &row.background,
+                        steps: &row.steps,
+                        after_hooks: &row.after_hooks,
+                        flaky: row.flaky,
+                        attempts: &row.attempts,
+                        duration: row.duration.as_ref(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FeatureReportData {
+    /// The runs of the scenarios of the feature and its rules, with the rule they are in
+    pub(crate) fn scenario_runs(&self) -> Vec<(Option<&RuleReportData>, ScenarioRun<'_>)> {
+        self.scenarios
+            .iter()
+            .map(|scenario| (None, scenario))
+            .chain(self.rules.iter().flat_map(|rule| {
+                rule.scenarios.iter().map(move |scenario| (Some(rule), scenario))
+            }))
+            .flat_map(|(rule, scenario)| scenario.runs().into_iter().map(move |run| (rule, run)))
+            .collect()
+    }
+}
+
+/// One run of a retried scenario, the steps include the hooks and background
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct AttemptRenderData {
+    pub attempt: usize,
+    pub state: StepState,
+    pub steps: Vec<StepRenderData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+/// The background of a feature or rule, the state of a step is
+/// combined over all scenarios it ran for
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct BackgroundRenderData {
+    pub keyword: String,
     pub name: String,
     pub description: String,
     pub steps: Vec<StepRenderData>,
@@ -63,14 +517,29 @@ pub(crate) struct ScenarioRenderData {
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct ExampleRowRenderData {
     pub example: Vec<String>,
+    pub before_hooks: Vec<StepRenderData>,
+    /// The background steps as they ran for this example
+    pub background: Vec<StepRenderData>,
     pub steps: Vec<StepRenderData>,
+    pub after_hooks: Vec<StepRenderData>,
     pub example_state: StepState,
+    pub flaky: bool,
+    pub attempts: Vec<AttemptRenderData>,
+    pub duration: Option<DurationRenderData>,
+}
+
+impl ExampleRowRenderData {
+    /// Everything that ran for the example in order, as for a scenario
+    pub(crate) fn all_steps(&self) -> Vec<StepRenderData> {
+        all_steps(&self.before_hooks, &self.background, &self.steps, &self.after_hooks)
+    }
 }
 
 #[derive(Serialize, Clone, Debug)]
 pub(crate) struct ExampleRenderData {
     pub name: String,
     pub description: String,
+    pub tags: Vec<String>,
     pub headers: Vec<String>,
     pub rows: Vec<ExampleRowRenderData>,
 }
@@ -79,6 +548,9 @@ pub(crate) struct ExampleRenderData {
 pub(crate) struct OutlineRenderData {
     pub name: String,
     pub scenario_description: String,
+    pub tags: Vec<String>,
+    /// Own, inherited and example tags, to filter on
+    pub all_tags: Vec<String>,
     pub examples: Vec<ExampleRenderData>,
     pub steps: Vec<StepRenderData>,
 }
@@ -88,8 +560,128 @@ pub(crate) struct OutlineRenderData {
 pub(crate) enum StepState {
     Passed,
     Failed,
-/// When a step is skipped,not run or a template step from outline
-/// also if a previous step has failed
+    /// Not run because a previous step, the background or the before hook did not pass
+    Skipped,
+    /// No step definition matches the step
+    Undefined,
+    /// More than one step definition matches the step
+    Ambiguous,
+/// When the scenario did not run or a template step from outline
     NotRun,
 }
 
+impl StepState {
+    /// The state of a scenario or example row, given the states of its steps
+    pub(crate) fn combined(states: &[StepState]) -> StepState {
+        match states {
+            states if states.contains(&StepState::Failed) => StepState::Failed,
+            states if states.contains(&StepState::Ambiguous) => StepState::Ambiguous,
+            states if states.contains(&StepState::Undefined) => StepState::Undefined,
+            states if states.iter().all(|state| state == &StepState::Passed) => StepState::Passed,
+            states if states.contains(&StepState::Skipped) => StepState::Skipped,
+            _ => StepState::NotRun,
+        }
+    }
+
+    /// Failed or ambiguous, the run fails on these
+    pub(crate) fn is_failure(&self) -> bool {
+        matches!(self, StepState::Failed | StepState::Ambiguous)
+    }
+}
+
+impl From<&StepError> for StepState {
+    fn from(error: &StepError) -> Self {
+        match error {
+            StepError::NotFound => StepState::Undefined,
+            StepError::AmbiguousMatch(_) => StepState::Ambiguous,
+            StepError::Panic(_) => StepState::Failed,
+        }
+    }
+}
+
+
+/// A compact markdown summary of the run, for a pull request comment or a job summary
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SummaryRenderData {
+    pub title: String,
+    pub status: StepState,
+    pub passed: bool,
+    pub duration: Option<DurationRenderData>,
+    pub nr_features: usize,
+    pub nr_scenarios: usize,
+    pub nr_steps: usize,
+    pub nr_errors: usize,
+    pub nr_undefined: usize,
+    pub nr_ambiguous: usize,
+    pub nr_skipped: usize,
+    pub nr_not_run: usize,
+    pub failures: Vec<SummaryFailureRenderData>,
+    pub parse_errors: Vec<ParseErrorRenderData>,
+    pub features: Vec<SummaryFeatureRenderData>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SummaryFeatureRenderData {
+    pub passed: bool,
+    #[serde(flatten)]
+    pub stats: FeatureRenderStatsData,
+    pub scenarios: Vec<SummaryScenarioRenderData>,
+}
+
+/// A scenario or an example row of an outline
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SummaryScenarioRenderData {
+    pub name: String,
+    pub state: StepState,
+    pub duration: Option<DurationRenderData>,
+}
+
+/// A scenario that did not pass, with its first step that did not pass
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct SummaryFailureRenderData {
+    pub feature: String,
+    pub scenario: String,
+    pub step: String,
+    pub message: Option<String>,
+    /// A code fence longer than any run of backticks in the message
+    pub fence: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct JunitRenderData {
+    pub tests: usize,
+    pub failures: usize,
+    pub skipped: usize,
+    pub time: f64,
+    pub suites: Vec<JunitSuiteRenderData>,
+}
+
+/// One testsuite per feature
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct JunitSuiteRenderData {
+    pub name: String,
+    pub tests: usize,
+    pub failures: usize,
+    pub skipped: usize,
+    pub time: f64,
+    pub cases: Vec<JunitCaseRenderData>,
+}
+
+/// One testcase per scenario, every example row of an outline is its own testcase
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct JunitCaseRenderData {
+    pub name: String,
+    pub classname: String,
+    pub time: f64,
+    pub state: StepState,
+    pub failure: Option<JunitFailureRenderData>,
+    /// Skipped, undefined or not run
+    pub skipped: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct JunitFailureRenderData {
+    pub kind: Option<StepErrorKind>,
+    pub message: String,
+    pub details: String,
+}
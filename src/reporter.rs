@@ -2,22 +2,38 @@ use cucumber::cli::Args;
 use cucumber::event::Cucumber::*;
 use cucumber::{
     Event,
-    event::{self},
+    event::{self, HookType, Retries},
     writer::Normalized,
 };
-use gherkin::{Examples, Feature, GherkinEnv, Scenario, Step};
+use gherkin::{Background, Examples, Feature, Rule, Scenario, Step};
 use handlebars::Handlebars;
 use rust_embed::Embed;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
     hash::{DefaultHasher, Hash, Hasher},
+    time::{Duration, SystemTime},
 };
 
 use crate::render_types::*;
 
+mod allure;
+mod builder;
+mod ctrf;
+mod error;
+mod junit;
+mod markdown;
+mod messages;
+mod outline;
+mod pdf;
+mod summary;
+
+pub use builder::{CucumberReporterBuilder, FilenameStrategy, ReportFormat};
+use builder::{ReporterConfig, ReporterSettings};
+use error::ReporterError;
+
 #[derive(Embed)]
 #[folder = "templates"]
 struct HtmlTemplates;
@@ -43,24 +59,132 @@ struct HtmlTemplates;
 pub struct CucumberReporter {
     features: HashSet<Arc<Feature>>,
     orig_features: HashSet<Arc<Feature>>,
-    step_states: HashMap<u64, StepState>,
+    /// Steps of the scenarios, keyed on the scenario since the steps of
+    /// example rows with the same values are the same
+    step_states: HashMap<Timed, StepState>,
+    step_errors: HashMap<Timed, StepErrorRenderData>,
+    /// Background steps and hooks, they run for every scenario
+    scenario_step_states: HashMap<Timed, StepState>,
+    scenario_step_errors: HashMap<Timed, StepErrorRenderData>,
+    /// Every attempt of a retried scenario, keyed on the scenario
+    attempts: HashMap<Timed, Vec<AttemptRenderData>>,
+    parse_errors: Vec<ParseErrorRenderData>,
+    /// Reporting problems, listed on stderr when the run is finished
+    problems: Vec<ReporterError>,
+    messages_failed: bool,
     outlines: HashSet<u64>,
-    nr_senarios: u32,
-    nr_rules: u32,
-    nr_steps: u32,
-    nr_errors: u32,
-    nr_skipped: u32,
+    /// Kept after the timer stopped, the reports show when something started
+    started: HashMap<Timed, SystemTime>,
+    durations: HashMap<Timed, Duration>,
+    messages: Option<messages::Messages>,
+    settings: ReporterSettings,
+    /// The settings merged with the commandline, resolved at the first event
+    config: Option<ReporterConfig>,
+}
+
+type Result<T> = std::result::Result<T, ReporterError>;
+
+/// Everything that is timed between its started and finished event.
+/// Scenarios are keyed on their position too, since the scenarios
+/// expanded from an outline share the span of the outline.
+/// Steps, background steps and hooks are keyed on the scenario they ran for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Timed {
+    Run,
+    Feature(u64),
+    Rule(u64, u64),
+    Scenario(u64, u64, usize),
+    Step(u64, u64, usize, u64),
+    Background(u64, u64, usize, u64),
+    BeforeHook(u64, u64, usize),
+    AfterHook(u64, u64, usize),
 }
 
-type Result<T> = std::result::Result<T, Box<dyn Error>>;
+impl Timed {
+    fn scenario(feature: &Feature, scenario: &Scenario) -> Self {
+        Timed::Scenario(feature.id(), scenario.id(), scenario.position.line)
+    }
+
+    fn step(feature: &Feature, scenario: &Scenario, step: &Step) -> Self {
+        Timed::Step(
+            feature.id(),
+            scenario.id(),
+            scenario.position.line,
+            step.id(),
+        )
+    }
+
+    fn background(feature: &Feature, scenario: &Scenario, step: &Step) -> Self {
+        Timed::Background(
+            feature.id(),
+            scenario.id(),
+            scenario.position.line,
+            step.id(),
+        )
+    }
+
+    fn hook(hook_type: HookType, feature: &Feature, scenario: &Scenario) -> Self {
+        match hook_type {
+            HookType::Before => {
+                Timed::BeforeHook(feature.id(), scenario.id(), scenario.position.line)
+            }
+            HookType::After => {
+                Timed::AfterHook(feature.id(), scenario.id(), scenario.position.line)
+            }
+        }
+    }
+
+    fn feature_id(&self) -> Option<u64> {
+        match self {
+            Timed::Run => None,
+            Timed::Feature(feature)
+            | Timed::Rule(feature, ..)
+            | Timed::Scenario(feature, ..)
+            | Timed::Step(feature, ..)
+            | Timed::Background(feature, ..)
+            | Timed::BeforeHook(feature, ..)
+            | Timed::AfterHook(feature, ..) => Some(*feature),
+        }
+    }
+}
 
 trait FeatureExt {
-    fn filename(&self) -> String;
+    /// Name of the html page, without collision handling
+    fn filename(&self, strategy: FilenameStrategy) -> String;
 }
 
 impl FeatureExt for Feature {
-    fn filename(&self) -> String {
-        format!("F{}.html", self.name.id())
+    fn filename(&self, strategy: FilenameStrategy) -> String {
+        match strategy {
+            FilenameStrategy::Slug => {
+                let source = match &self.path {
+                    Some(path) => display_path(&path.with_extension("")),
+                    None => self.name.clone(),
+                };
+                format!("{}.html", slug(&source))
+            }
+            FilenameStrategy::Hashed => format!("F{}.html", self.name.id()),
+        }
+    }
+}
+
+/// Lowercase `[a-z0-9_-]` only, the name ends up in hrefs and in javascript
+/// strings of the templates. Path separators become `_`, any other run of
+/// characters a single `-`.
+fn slug(source: &str) -> String {
+    let mut slug = String::new();
+    for c in source.to_lowercase().chars() {
+        match c {
+            'a'..='z' | '0'..='9' | '_' => slug.push(c),
+            '/' | '\\' => slug.push('_'),
+            _ if !slug.ends_with('-') => slug.push('-'),
+            _ => {}
+        }
+    }
+    let slug = slug.trim_matches('-');
+    match slug.is_empty() {
+        true => "feature".to_string(),
+        false => slug.to_string(),
     }
 }
 
@@ -73,6 +197,21 @@ trait ToId: Hash {
 }
 
 impl ToId for Step {}
+impl ToId for Feature {
+    fn id(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.path.hash(&mut hasher);
+        self.name.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+impl ToId for Rule {
+    fn id(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.span.hash(&mut hasher);
+        hasher.finish()
+    }
+}
 impl ToId for Examples {
     fn id(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
@@ -101,297 +240,793 @@ impl CucumberReporter {
             features: HashSet::new(),
             orig_features: HashSet::new(),
             step_states: HashMap::new(),
+            step_errors: HashMap::new(),
+            scenario_step_states: HashMap::new(),
+            scenario_step_errors: HashMap::new(),
+            attempts: HashMap::new(),
+            parse_errors: Vec::new(),
+            problems: Vec::new(),
+            messages_failed: false,
             outlines: HashSet::new(),
-            nr_errors: 0,
-            nr_rules: 0,
-            nr_senarios: 0,
-            nr_skipped: 0,
-            nr_steps: 0,
+            started: HashMap::new(),
+            durations: HashMap::new(),
+            messages: None,
+            settings: ReporterSettings::default(),
+            config: None,
         }
     }
 
+    /// Configure the reporter in code, see [`CucumberReporterBuilder`]
+    pub fn builder() -> CucumberReporterBuilder {
+        CucumberReporterBuilder::default()
+    }
+
     fn add_feature(&mut self, feature: Arc<Feature>) {
         if !self.features.contains(&feature)
             && self.features.insert(feature.clone())
-            && feature.scenarios.iter().any(|s| !s.examples.is_empty())
+            && scenarios_with_rule(&feature).any(|(_, s)| !s.examples.is_empty())
         {
-            let org = Feature::parse_path(
-                feature.path.clone().expect("Feature not found"),
-                GherkinEnv::default(),
-            )
-            .expect("Failed to parse feature");
-            self.orig_features.insert(Arc::new(org));
+            self.orig_features
+                .insert(Arc::new(outline::merged_feature(&feature)));
         }
     }
 
-    fn add_step(&mut self, step: Arc<Step>, state: StepState) {
-        self.step_states.insert(step.id(), state);
+    fn step_render(&self, feature: &Feature, scenario: &Scenario, step: &Step) -> StepRenderData {
+        let timed = Timed::step(feature, scenario, step);
+        StepRenderData::new(
+            step,
+            self.step_states
+                .get(&timed)
+                .unwrap_or(&StepState::NotRun)
+                .clone(),
+        )
+        .with_error(self.step_errors.get(&timed).cloned())
+        .with_duration(self.duration(timed))
     }
 
-    async fn finish(&mut self, args: &ReporterArgs) -> Result<()> {
-        let mut templates = Handlebars::new();
-        templates.register_embed_templates::<HtmlTemplates>()?;
+    /// The background steps of a scenario, as they ran for it
+    fn background_render(&self, feature: &Feature, scenario: &Scenario) -> Vec<StepRenderData> {
+        backgrounds(feature, scenario)
+            .into_iter()
+            .flat_map(|background| {
+                grouped(background.steps.iter().map(|step| {
+                    let timed = Timed::background(feature, scenario, step);
+                    StepRenderData::new(
+                        step,
+                        self.scenario_step_states
+                            .get(&timed)
+                            .unwrap_or(&StepState::NotRun)
+                            .clone(),
+                    )
+                    .with_error(self.scenario_step_errors.get(&timed).cloned())
+                    .with_duration(self.duration(timed))
+                }))
+            })
+            .collect()
+    }
 
-        let mut index_data = Vec::new();
+    /// A hook as a pseudo step, only when it ran for the scenario
+    fn hook_render(
+        &self,
+        hook_type: HookType,
+        feature: &Feature,
+        scenario: &Scenario,
+    ) -> Vec<StepRenderData> {
+        let timed = Timed::hook(hook_type, feature, scenario);
+        self.scenario_step_states
+            .get(&timed)
+            .map(|state| {
+                StepRenderData::hook(hook_type, state.clone())
+                    .with_error(self.scenario_step_errors.get(&timed).cloned())
+                    .with_duration(self.duration(timed))
+            })
+            .into_iter()
+            .collect()
+    }
 
-        let features = self.features.clone();
-        for feature in features {
-            let mut scenarios = Vec::new();
-            for scenario in &feature.scenarios {
-                let scenario_html = self
-                    .scenario_render(&templates, feature.clone(), scenario)
-                    .await?
-                    .clone();
-                scenarios.push(scenario_html.to_string());
+    /// Everything that ran for a scenario in order: the before hook,
+    /// the background steps, the steps and the after hook
+    fn scenario_render(&self, feature: &Feature, scenario: &Scenario) -> Vec<StepRenderData> {
+        self.hook_render(HookType::Before, feature, scenario)
+            .into_iter()
+            .chain(self.background_render(feature, scenario))
+            .chain(grouped(
+                scenario
+                    .steps
+                    .iter()
+                    .map(|step| self.step_render(feature, scenario, step)),
+            ))
+            .chain(self.hook_render(HookType::After, feature, scenario))
+            .collect()
+    }
+
+    /// The attempts of a scenario, only when it was retried
+    fn attempts(&self, feature: &Feature, scenario: &Scenario) -> Vec<AttemptRenderData> {
+        self.attempts
+            .get(&Timed::scenario(feature, scenario))
+            .filter(|attempts| attempts.len() > 1)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Passed after one or more failed attempts
+    fn flaky(&self, feature: &Feature, scenario: &Scenario) -> bool {
+        let attempts = self.attempts(feature, scenario);
+        attempts
+            .last()
+            .is_some_and(|attempt| attempt.state == StepState::Passed)
+            && attempts.len() > 1
+    }
+
+    fn add_attempt(&mut self, feature: &Feature, scenario: &Scenario, retries: Retries) {
+        let attempt = AttemptRenderData {
+            attempt: retries.current + 1,
+            state: self.scenario_state(feature, scenario),
+            steps: self.scenario_render(feature, scenario),
+            duration: self.duration(Timed::scenario(feature, scenario)),
+        };
+        self.attempts
+            .entry(Timed::scenario(feature, scenario))
+            .or_default()
+            .push(attempt);
+    }
+
+    /// Steps of a finished scenario without an outcome were skipped,
+    /// because an earlier step or the before hook did not pass
+    fn mark_skipped(&mut self, feature: &Feature, scenario: &Scenario) {
+        for step in &scenario.steps {
+            self.step_states
+                .entry(Timed::step(feature, scenario, step))
+                .or_insert(StepState::Skipped);
+        }
+        for step in backgrounds(feature, scenario)
+            .into_iter()
+            .flat_map(|background| background.steps.iter())
+        {
+            self.scenario_step_states
+                .entry(Timed::background(feature, scenario, step))
+                .or_insert(StepState::Skipped);
+        }
+    }
+
+    /// A retry starts without the results of the previous attempt,
+    /// those are kept in the attempts
+    fn clear_attempt(&mut self, feature: &Feature, scenario: &Scenario) {
+        for step in &scenario.steps {
+            let timed = Timed::step(feature, scenario, step);
+            self.step_states.remove(&timed);
+            self.step_errors.remove(&timed);
+            self.durations.remove(&timed);
+        }
+        let timed = backgrounds(feature, scenario)
+            .into_iter()
+            .flat_map(|background| background.steps.iter())
+            .map(|step| Timed::background(feature, scenario, step))
+            .chain([
+                Timed::hook(HookType::Before, feature, scenario),
+                Timed::hook(HookType::After, feature, scenario),
+            ])
+            .collect::<Vec<_>>();
+        for timed in timed {
+            self.scenario_step_states.remove(&timed);
+            self.scenario_step_errors.remove(&timed);
+            self.durations.remove(&timed);
+        }
+    }
+
+    /// A background of a feature or rule, the state of each step combined
+    /// over the scenarios it ran for
+    fn background_data(
+        &self,
+        feature: &Feature,
+        background: &Background,
+        scenarios: &[&Scenario],
+    ) -> BackgroundRenderData {
+        let steps = background
+            .steps
+            .iter()
+            .map(|step| {
+                let states = scenarios
+                    .iter()
+                    .filter_map(|scenario| {
+                        self.scenario_step_states
+                            .get(&Timed::background(feature, scenario, step))
+                            .cloned()
+                    })
+                    .collect::<Vec<_>>();
+                let state = if states.is_empty() {
+                    StepState::NotRun
+                } else {
+                    StepState::combined(&states)
+                };
+                StepRenderData::new(step, state)
+            })
+            .collect::<Vec<_>>();
+        BackgroundRenderData {
+            keyword: background.keyword.clone(),
+            name: background.name.clone(),
+            description: background.description.clone().unwrap_or_default(),
+            steps: grouped(steps),
+        }
+    }
+
+    /// Outcome of the executed scenarios per tag, sorted by tag
+    fn tag_stats(&self) -> Vec<TagStatsRenderData> {
+        let mut stats = BTreeMap::<String, TagStatsRenderData>::new();
+        for feature in &self.features {
+            for (rule, scenario) in scenarios_with_rule(feature) {
+                let state = self.scenario_state(feature, scenario);
+                let tags = merge_tags(
+                    feature
+                        .tags
+                        .iter()
+                        .chain(rule.iter().flat_map(|r| r.tags.iter()))
+                        .chain(scenario.tags.iter()),
+                );
+                for tag in tags {
+                    let tag_stats = stats.entry(tag.clone()).or_insert(TagStatsRenderData {
+                        name: tag,
+                        ..Default::default()
+                    });
+                    tag_stats.nr_scenarios += 1;
+                    match state {
+                        StepState::Passed => tag_stats.nr_passed += 1,
+                        StepState::Failed | StepState::Ambiguous => tag_stats.nr_failed += 1,
+                        StepState::Undefined => tag_stats.nr_undefined += 1,
+                        StepState::Skipped | StepState::NotRun => tag_stats.nr_skipped += 1,
+                    }
+                }
             }
-            let mut rules = Vec::new();
-            for rule in &feature.rules {
-                let rules_html = self
-                    .render_rule(&templates, feature.clone(), rule)
-                    .await?
-                    .clone();
-                rules.push(rules_html.to_string());
+        }
+        stats.into_values().collect()
+    }
+
+    /// A scenario fails when its background or one of its hooks fails
+    fn scenario_state(&self, feature: &Feature, scenario: &Scenario) -> StepState {
+        StepState::combined(
+            &self
+                .scenario_render(feature, scenario)
+                .into_iter()
+                .map(|step| step.step_state)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Number of steps of the scenarios in the given state
+    fn nr_steps_with(&self, feature: &Feature, scenarios: &[&Scenario], state: StepState) -> usize {
+        scenarios
+            .iter()
+            .flat_map(|scenario| {
+                scenario
+                    .steps
+                    .iter()
+                    .map(move |step| Timed::step(feature, scenario, step))
+            })
+            .filter(|timed| self.step_states.get(timed).unwrap_or(&StepState::NotRun) == &state)
+            .count()
+    }
+
+    /// Number of failed background steps or hooks of a feature
+    fn nr_failed(&self, feature: &Feature, filter: impl Fn(&Timed) -> bool) -> usize {
+        self.scenario_step_states
+            .iter()
+            .filter(|(timed, state)| {
+                timed.feature_id() == Some(feature.id())
+                    && filter(timed)
+                    && **state == StepState::Failed
+            })
+            .count()
+    }
+
+    fn start_timer(&mut self, timed: Timed, at: SystemTime) {
+        self.started.insert(timed, at);
+    }
+
+    fn stop_timer(&mut self, timed: Timed, at: SystemTime) {
+        if let Some(started) = self.started.get(&timed) {
+            self.durations
+                .insert(timed, at.duration_since(*started).unwrap_or_default());
+        }
+    }
+
+    fn duration(&self, timed: Timed) -> Option<DurationRenderData> {
+        self.durations.get(&timed).map(|duration| {
+            DurationRenderData::from(duration).with_start(self.started.get(&timed).copied())
+        })
+    }
+
+    /// Writes every report, a report that fails does not stop the others
+    async fn finish(&mut self, config: ReporterConfig) {
+        // without templates the outputs rendered by them are skipped, the others
+        // are still written
+        let templates = match self.templates(&config) {
+            Ok(templates) => Some(templates),
+            Err(error) => {
+                self.problems.push(error);
+                None
             }
+        };
+
+        let report = self.report_data(&config);
+
+        let mut results = Vec::new();
+        if config.html
+            && let Some(templates) = &templates
+        {
+            for feature in &report.features {
+                results.push(write_feature_page(&config, templates, feature));
+            }
+            results.push(write_index(&config, templates, &report));
+        }
+
+        if config.json {
+            results.push(
+                serde_json::to_string_pretty(&report)
+                    .map_err(ReporterError::from)
+                    .and_then(|json| write_html_file(&config, json, "report.json".to_string())),
+            );
+        }
+
+        if let (Some(path), Some(templates)) = (&config.junit_path, &templates) {
+            results.push(
+                junit::junit_render(templates, &report)
+                    .and_then(|junit_xml| write_file(path, junit_xml)),
+            );
+        }
+
+        if let (Some(path), Some(templates)) = (&config.single_html_path, &templates) {
+            results.push(write_single_file(&config, templates, &report, path));
+        }
+
+        if let (Some(path), Some(templates)) = (&config.markdown_path, &templates) {
+            results.push(
+                summary::summary_render(templates, &config, &report)
+                    .and_then(|markdown| write_file(path, markdown)),
+            );
+        }
+
+        if let Some(dir) = &config.allure_dir {
+            results.push(allure::write_allure_results(dir, &report));
+        }
 
-            let data = FeatureRenderData {
-                name: feature.name.clone(),
-                description: feature.description.clone().unwrap_or_default(),
-                scenarios: scenarios.join(""),
-                rules: rules.join(""),
+        if let Some(path) = &config.ctrf_path {
+            results
+                .push(ctrf::ctrf_render(&config, &report).and_then(|json| write_file(path, json)));
+        }
+
+        if let Some(path) = &config.pdf_path {
+            results.push(write_file(path, pdf::pdf_render(&config.title, &report)));
+        }
+        self.problems
+            .extend(results.into_iter().filter_map(|result| result.err()));
+    }
+
+    /// Reporting problems do not fail the run, they are listed on stderr
+    fn print_problems(&self) {
+        if self.problems.is_empty() {
+            return;
+        }
+        eprintln!(
+            "cucumber-reporter: {} problem(s) while writing the reports:",
+            self.problems.len()
+        );
+        for problem in &self.problems {
+            eprintln!("  - {problem}");
+        }
+    }
+
+    /// The embedded templates, replaced by the ones in the template directory.
+    /// A template there that does not compile is a problem, the embedded one
+    /// is used instead.
+    fn templates(&mut self, config: &ReporterConfig) -> Result<Handlebars<'static>> {
+        let mut templates = Handlebars::new();
+        templates.register_embed_templates::<HtmlTemplates>()?;
+        templates.register_helper(
+            "description",
+            Box::new(markdown::DescriptionHelper {
+                markdown: config.markdown_descriptions,
+            }),
+        );
+
+        if let Some(template_dir) = &config.template_dir {
+            let entries = match std::fs::read_dir(template_dir) {
+                Ok(entries) => entries,
+                Err(source) => {
+                    self.problems.push(ReporterError::TemplateDir {
+                        path: template_dir.clone(),
+                        source,
+                    });
+                    return Ok(templates);
+                }
             };
-            let feature_html = templates.render("feature.html", &data)?;
-            let html = templates
-                .render("page.html", &feature_html)
-                .expect("Failed to render page.html");
-            write_html_file(args, html, feature.filename())?;
-
-            let all_scenarios = feature
-                .scenarios
-                .iter()
-                .chain(feature.rules.iter().flat_map(|r| r.scenarios.iter()))
+            let mut paths = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file())
                 .collect::<Vec<_>>();
+            paths.sort();
+            for path in paths {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                // a misspelled name would silently keep the embedded template
+                if HtmlTemplates::get(&name).is_none() {
+                    self.problems.push(ReporterError::UnknownTemplate {
+                        path: path.clone(),
+                        templates: HtmlTemplates::iter().map(String::from).collect(),
+                    });
+                } else if let Err(error) = templates.register_template_file(&name, &path) {
+                    self.problems.push(error.into());
+                }
+            }
+        }
+        Ok(templates)
+    }
 
-            index_data.push(FeatureRenderStatsData {
-                name: feature.name.clone(),
-                link: feature.filename(),
-                description: feature.description.clone().unwrap_or_default(),
-                nr_scenarios: all_scenarios.len(),
-                nr_rules: feature.rules.iter().count().into(),
-                nr_steps: all_scenarios.iter().map(|s| s.steps.iter().count()).sum(),
-                nr_errors: all_scenarios
-                    .iter()
-                    .map(|s| {
-                        s.steps
-                            .iter()
-                            .filter(|st| {
-                                self.step_states
-                                    .get(&st.id())
-                                    .is_some_and(|ss| ss == &StepState::Failed)
-                            })
-                            .count()
-                    })
-                    .sum(),
-                nr_skipped: all_scenarios
-                    .iter()
-                    .map(|s| {
-                        s.steps
-                            .iter()
-                            .filter(|st| {
-                                self.step_states
-                                    .get(&st.id())
-                                    .is_none_or(|ss| ss == &StepState::NotRun)
-                            })
-                            .count()
-                    })
-                    .sum(),
+    /// The html page of every feature. Features are sorted on path and name,
+    /// so a number added to a duplicate name is the same in every run.
+    fn filenames(&self, strategy: FilenameStrategy) -> HashMap<u64, String> {
+        let mut features = self.features.iter().collect::<Vec<_>>();
+        features.sort_by(|a, b| (&a.path, &a.name).cmp(&(&b.path, &b.name)));
+
+        let mut taken = HashSet::from(["index.html".to_string()]);
+        let mut filenames = HashMap::new();
+        for feature in features {
+            let filename = feature.filename(strategy);
+            let mut unique = filename.clone();
+            let mut nr = 1;
+            while !taken.insert(unique.clone()) {
+                nr += 1;
+                unique = format!("{}-{nr}.html", filename.trim_end_matches(".html"));
+            }
+            filenames.insert(feature.id(), unique);
+        }
+        filenames
+    }
+
+    /// Collects everything that is reported, sorted by feature name and page.
+    /// A feature that can not be reported gets a page with the problem.
+    fn report_data(&mut self, config: &ReporterConfig) -> ReportData {
+        let filenames = self.filenames(config.filename_strategy);
+        let mut features = Vec::new();
+        for feature in self.features.clone() {
+            let link = filenames.get(&feature.id()).cloned().unwrap_or_default();
+            match self.feature_data(feature.clone(), link.clone()) {
+                Ok(data) => features.push(data),
+                Err(error) => {
+                    features.push(feature_error_data(&feature, link, &error));
+                    self.problems.push(error);
+                }
+            }
+        }
+        features
+            .sort_by(|a, b| (&a.stats.name, &a.stats.link).cmp(&(&b.stats.name, &b.stats.link)));
+        let failed = !self.parse_errors.is_empty()
+            || features.iter().any(|f| {
+                f.stats.nr_errors > 0
+                    || f.stats.nr_ambiguous > 0
+                    || f.stats.nr_background_errors > 0
             });
+        ReportData {
+            status: if failed {
+                StepState::Failed
+            } else {
+                StepState::Passed
+            },
+            features,
+            parse_errors: self.parse_errors.clone(),
+            tags: self.tag_stats(),
+            duration: self.duration(Timed::Run),
         }
-        index_data.sort_by_key(|f| f.name.clone());
-        let data = IndexRenderData {
-            features: index_data.to_vec(),
+    }
+
+    fn feature_data(&mut self, feature: Arc<Feature>, link: String) -> Result<FeatureReportData> {
+        let mut scenarios = Vec::new();
+        for scenario in &feature.scenarios {
+            if let Some(data) = self.scenario_data(feature.clone(), None, scenario)? {
+                scenarios.push(data);
+            }
+        }
+        let mut rules = Vec::new();
+        for rule in &feature.rules {
+            rules.push(self.rule_data(feature.clone(), rule)?);
+        }
+
+        let all_scenarios = feature
+            .scenarios
+            .iter()
+            .chain(feature.rules.iter().flat_map(|r| r.scenarios.iter()))
+            .collect::<Vec<_>>();
+        let background = feature
+            .background
+            .as_ref()
+            .map(|b| self.background_data(&feature, b, &all_scenarios));
+
+        let stats = FeatureRenderStatsData {
+            name: feature.name.clone(),
+            link,
+            description: feature.description.clone().unwrap_or_default(),
+            tags: feature.tags.clone(),
+            all_tags: merge_tags(
+                feature
+                    .tags
+                    .iter()
+                    .chain(feature.rules.iter().flat_map(|r| r.tags.iter()))
+                    .chain(all_scenarios.iter().flat_map(|s| s.tags.iter())),
+            ),
+            nr_scenarios: all_scenarios.len(),
+            nr_rules: feature.rules.len(),
+            nr_steps: all_scenarios.iter().map(|s| s.steps.len()).sum(),
+            nr_errors: self.nr_steps_with(&feature, &all_scenarios, StepState::Failed)
+                + self.nr_failed(&feature, |timed| {
+                    matches!(timed, Timed::BeforeHook(..) | Timed::AfterHook(..))
+                }),
+            nr_undefined: self.nr_steps_with(&feature, &all_scenarios, StepState::Undefined),
+            nr_ambiguous: self.nr_steps_with(&feature, &all_scenarios, StepState::Ambiguous),
+            nr_background_errors: self
+                .nr_failed(&feature, |timed| matches!(timed, Timed::Background(..))),
+            nr_flaky: all_scenarios
+                .iter()
+                .filter(|s| self.flaky(&feature, s))
+                .count(),
+            nr_skipped: self.nr_steps_with(&feature, &all_scenarios, StepState::Skipped),
+            nr_not_run: self.nr_steps_with(&feature, &all_scenarios, StepState::NotRun),
+            duration: self.duration(Timed::Feature(feature.id())),
+            error: None,
         };
-        let index_html = templates.render("index.html", &data)?;
-        write_html_file(args, index_html, "index.html".to_string())?;
-        Ok(())
+
+        Ok(FeatureReportData {
+            stats,
+            background,
+            rules,
+            scenarios,
+        })
     }
 
-    async fn render_rule(
-        &mut self,
-        templates: &Handlebars<'_>,
-        feature: Arc<Feature>,
-        rule: &gherkin::Rule,
-    ) -> Result<String> {
+    fn rule_data(&mut self, feature: Arc<Feature>, rule: &gherkin::Rule) -> Result<RuleReportData> {
         let mut scenarios = Vec::new();
         for scenario in &rule.scenarios {
-            let scenario_html = self
-                .scenario_render(templates, feature.clone(), scenario)
-                .await?;
-            scenarios.push(scenario_html);
+            if let Some(data) = self.scenario_data(feature.clone(), Some(rule), scenario)? {
+                scenarios.push(data);
+            }
         }
-        let data = RuleRenderData {
+        Ok(RuleReportData {
             name: rule.name.clone(),
             description: rule.description.clone().unwrap_or_default(),
-            scenarios: scenarios.join(""),
-        };
-        let rules_html = templates.render("rule.html", &data)?;
-        Ok(rules_html)
+            tags: rule.tags.clone(),
+            background: rule.background.as_ref().map(|b| {
+                self.background_data(&feature, b, &rule.scenarios.iter().collect::<Vec<_>>())
+            }),
+            scenarios,
+            duration: self.duration(Timed::Rule(feature.id(), rule.id())),
+        })
     }
 
-    async fn scenario_render(
+    /// Scenarios expanded from an outline are reported once, as the outline.
+    /// `None` is returned for the already reported ones.
+    fn scenario_data(
         &mut self,
-        templates: &Handlebars<'_>,
         feature: Arc<Feature>,
+        rule: Option<&Rule>,
         scenario: &gherkin::Scenario,
-    ) -> Result<String> {
+    ) -> Result<Option<ScenarioReportData>> {
+        let inherited_tags = merge_tags(
+            feature
+                .tags
+                .iter()
+                .chain(rule.iter().flat_map(|r| r.tags.iter())),
+        );
         if !scenario.examples.is_empty() {
-            let org_feature = self
+            let org_scenario = self
                 .orig_features
                 .iter()
-                .find(|f| f.name == feature.name)
-                .expect("Original feature not found");
-
-            let org_scenario = org_feature
-                .scenarios
-                .iter()
-                .find(|s| s.id() == scenario.id())
-                .expect("Original scenario not found")
-                .clone();
+                .find(|f| f.id() == feature.id())
+                .and_then(|f| scenarios_with_rule(f).find(|(_, s)| s.id() == scenario.id()))
+                .map(|(_, s)| s.clone())
+                .ok_or_else(|| ReporterError::OutlineNotFound {
+                    feature: feature.name.clone(),
+                    scenario: scenario.name.clone(),
+                })?;
 
             if self.outline_processed(&org_scenario) {
-                let example_ids = org_scenario
-                    .examples
-                    .iter()
-                    .map(|e| e.id())
-                    .collect::<Vec<_>>();
-
-                let all_scenarios = feature
-                    .scenarios
-                    .iter()
-                    .filter(|s| {
-                        s.examples
-                            .iter()
-                            .map(|e| e.id())
-                            .all(|f| example_ids.contains(&f))
-                    })
-                    .collect::<Vec<_>>();
-
                 let data = OutlineRenderData {
                     name: org_scenario.name.clone(),
                     scenario_description: org_scenario.description.clone().unwrap_or_default(),
+                    tags: org_scenario.tags.clone(),
+                    all_tags: merge_tags(
+                        inherited_tags
+                            .iter()
+                            .chain(org_scenario.tags.iter())
+                            .chain(org_scenario.examples.iter().flat_map(|ex| ex.tags.iter())),
+                    ),
                     examples: org_scenario
                         .examples
                         .iter()
                         .map(|ex| {
-                            let table = ex.table.clone().expect("table expected").clone();
-                            ExampleRenderData {
+                            let (headers, rows) = ex
+                                .table
+                                .as_ref()
+                                .and_then(|table| table.rows.split_first())
+                                .ok_or_else(|| ReporterError::ExamplesWithoutTable {
+                                    feature: feature.name.clone(),
+                                    scenario: org_scenario.name.clone(),
+                                })?;
+                            let expanded = expanded_rows(&feature, ex);
+                            Ok(ExampleRenderData {
                                 name: ex.name.clone().unwrap_or_default(),
                                 description: ex.description.clone().unwrap_or_default(),
-                                headers: table.rows.first().expect("First row not found").clone(),
-                                rows: table
-                                    .rows
+                                tags: ex.tags.clone(),
+                                headers: headers.clone(),
+                                rows: rows
                                     .iter()
-                                    .skip(1)
                                     .enumerate()
                                     .map(|(id, row)| {
-                                        self.new_example_row(&all_scenarios, ex, id, row)
+                                        self.new_example_row(
+                                            &feature,
+                                            &org_scenario,
+                                            expanded.get(id).copied(),
+                                            ex,
+                                            id,
+                                            row,
+                                        )
                                     })
-                                    .collect::<Vec<_>>(),
-                            }
+                                    .collect::<Result<Vec<_>>>()?,
+                            })
                         })
-                        .collect::<Vec<_>>(),
-                    steps: org_scenario
-                        .steps
-                        .iter()
-                        .map(|s| StepRenderData::new(s, StepState::NotRun))
-                        .collect(),
+                        .collect::<Result<Vec<_>>>()?,
+                    steps: grouped(
+                        org_scenario
+                            .steps
+                            .iter()
+                            .map(|s| StepRenderData::new(s, StepState::NotRun)),
+                    ),
                 };
-                let scenario_html = templates.render("outline.html", &data)?;
-                Ok(scenario_html.to_string())
+                Ok(Some(ScenarioReportData::Outline(data)))
             } else {
-                Ok("".to_string())
+                Ok(None)
             }
         } else {
             let data = ScenarioRenderData {
                 name: scenario.name.clone(),
                 description: scenario.description.clone().unwrap_or_default(),
-                steps: scenario
-                    .steps
-                    .iter()
-                    .map(|s| {
-                        StepRenderData::new(
-                            s,
-                            self.step_states
-                                .get(&s.id())
-                                .unwrap_or(&StepState::NotRun)
-                                .clone(),
-                        )
-                    })
-                    .collect(),
+                tags: scenario.tags.clone(),
+                all_tags: merge_tags(inherited_tags.iter().chain(scenario.tags.iter())),
+                before_hooks: self.hook_render(HookType::Before, &feature, scenario),
+                background: self.background_render(&feature, scenario),
+                steps: grouped(
+                    scenario
+                        .steps
+                        .iter()
+                        .map(|s| self.step_render(&feature, scenario, s)),
+                ),
+                after_hooks: self.hook_render(HookType::After, &feature, scenario),
+                flaky: self.flaky(&feature, scenario),
+                attempts: self.attempts(&feature, scenario),
+                duration: self.duration(Timed::scenario(&feature, scenario)),
             };
-            let scenario_html = templates.render("scenario.html", &data)?;
-            Ok(scenario_html)
+            Ok(Some(ScenarioReportData::Scenario(data)))
         }
     }
 
+    /// `expanded` is the scenario cucumber ran for the row, checked on the values of the row
     fn new_example_row(
         &mut self,
-        all_scenarios: &Vec<&Scenario>,
+        feature: &Feature,
+        outline: &Scenario,
+        expanded: Option<&Scenario>,
         ex: &Examples,
         id: usize,
-        row: &Vec<String>,
-    ) -> ExampleRowRenderData {
-        let scenario_id = ex.position.line + 2 + id;
-        let scenario = all_scenarios
-            .iter()
-            .find(|s| s.position.line == scenario_id)
-            .expect("Scenario not found");
-        let steps = scenario
-            .steps
-            .iter()
-            .map(|step| {
-                StepRenderData::new(
-                    step,
-                    self.step_states
-                        .get(&step.id())
-                        .unwrap_or(&StepState::NotRun)
-                        .clone(),
-                )
+        row: &[String],
+    ) -> Result<ExampleRowRenderData> {
+        let scenario = expanded
+            .filter(|s| {
+                s.examples
+                    .first()
+                    .and_then(|e| e.table.as_ref())
+                    .and_then(|table| table.rows.get(1))
+                    .is_some_and(|values| values.as_slice() == row)
             })
-            .collect::<Vec<_>>();
-        let example_state = match steps
-            .iter()
-            .map(|step| step.step_state.clone())
-            .collect::<Vec<_>>()
-        {
-            states if states.iter().any(|state| state == &StepState::Failed) => StepState::Failed,
-            states if states.iter().all(|state| state == &StepState::Passed) => StepState::Passed,
-            states if states.iter().any(|state| state == &StepState::NotRun) => StepState::NotRun,
-            _ => todo!(),
-        };
-        ExampleRowRenderData {
-            example: row.clone(),
+            .ok_or_else(|| ReporterError::ExampleNotFound {
+                feature: feature.name.clone(),
+                scenario: outline.name.clone(),
+                examples_line: ex.position.line,
+                row: id + 1,
+            })?;
+        let steps = grouped(
+            scenario
+                .steps
+                .iter()
+                .map(|step| self.step_render(feature, scenario, step)),
+        );
+        Ok(ExampleRowRenderData {
+            example: row.to_vec(),
+            before_hooks: self.hook_render(HookType::Before, feature, scenario),
+            background: self.background_render(feature, scenario),
             steps,
-            example_state,
-        }
+            after_hooks: self.hook_render(HookType::After, feature, scenario),
+            example_state: self.scenario_state(feature, scenario),
+            flaky: self.flaky(feature, scenario),
+            attempts: self.attempts(feature, scenario),
+            duration: self.duration(Timed::scenario(feature, scenario)),
+        })
     }
 
-    fn process_scenario<W>(&mut self, event: event::RetryableScenario<W>) {
-        self.nr_senarios += 1;
-        if let event::Scenario::Step(gherkin_step, event) = event.event {
-            self.nr_steps += 1;
-            match event {
-                event::Step::Passed(_capture_locations, _location) => {
-                    self.add_step(gherkin_step.into(), StepState::Passed);
+    fn process_scenario<W>(
+        &mut self,
+        feature: &Feature,
+        scenario: &Scenario,
+        event: event::RetryableScenario<W>,
+        at: SystemTime,
+    ) {
+        let retries = event.retries;
+        match event.event {
+            event::Scenario::Started => {
+                if retries.is_some_and(|r| r.current > 0) {
+                    self.clear_attempt(feature, scenario);
+                }
+                self.start_timer(Timed::scenario(feature, scenario), at)
+            }
+            event::Scenario::Finished => {
+                self.stop_timer(Timed::scenario(feature, scenario), at);
+                self.mark_skipped(feature, scenario);
+                if let Some(retries) = retries {
+                    self.add_attempt(feature, scenario, retries);
                 }
-                event::Step::Failed(_capture_locations, _location, _world, _step_error) => {
-                    self.nr_errors += 1;
-                    self.add_step(gherkin_step.into(), StepState::Failed);
+            }
+            event::Scenario::Step(gherkin_step, event) => {
+                let timed = Timed::step(feature, scenario, &gherkin_step);
+                match event {
+                    event::Step::Started => self.start_timer(timed, at),
+                    event::Step::Passed(_capture_locations, _location) => {
+                        self.stop_timer(timed, at);
+                        self.step_states.insert(timed, StepState::Passed);
+                    }
+                    event::Step::Failed(_capture_locations, _location, _world, step_error) => {
+                        self.stop_timer(timed, at);
+                        self.step_errors.insert(timed, (&step_error).into());
+                        self.step_states.insert(timed, (&step_error).into());
+                    }
+                    event::Step::Skipped => {
+                        self.stop_timer(timed, at);
+                        self.step_states.insert(timed, StepState::Undefined);
+                    }
+                }
+            }
+            event::Scenario::Hook(hook_type, event) => {
+                let timed = Timed::hook(hook_type, feature, scenario);
+                match event {
+                    event::Hook::Started => self.start_timer(timed, at),
+                    event::Hook::Passed => {
+                        self.stop_timer(timed, at);
+                        self.scenario_step_states.insert(timed, StepState::Passed);
+                    }
+                    event::Hook::Failed(_world, info) => {
+                        self.stop_timer(timed, at);
+                        self.scenario_step_errors.insert(timed, (&info).into());
+                        self.scenario_step_states.insert(timed, StepState::Failed);
+                    }
                 }
-                event::Step::Skipped => {
-                    self.nr_skipped += 1;
+            }
+            event::Scenario::Background(gherkin_step, event) => {
+                let timed = Timed::background(feature, scenario, &gherkin_step);
+                match event {
+                    event::Step::Started => self.start_timer(timed, at),
+                    event::Step::Passed(_capture_locations, _location) => {
+                        self.stop_timer(timed, at);
+                        self.scenario_step_states.insert(timed, StepState::Passed);
+                    }
+                    event::Step::Failed(_capture_locations, _location, _world, step_error) => {
+                        self.stop_timer(timed, at);
+                        self.scenario_step_errors
+                            .insert(timed, (&step_error).into());
+                        self.scenario_step_states
+                            .insert(timed, (&step_error).into());
+                    }
+                    event::Step::Skipped => {
+                        self.stop_timer(timed, at);
+                        self.scenario_step_states
+                            .insert(timed, StepState::Undefined);
+                    }
                 }
-                _ => {}
             }
+            _ => {}
         }
     }
 
@@ -400,21 +1035,298 @@ impl CucumberReporter {
     }
 }
 
-fn write_html_file(args: &ReporterArgs, html: String, filename: String) -> Result<()> {
-    let filename = if let Some(path) = &args.output_html_path {
-        std::fs::create_dir_all(path)?;
-        format!("{}/{}", path, filename)
+/// All scenarios of a feature, with the rule they are part of
+fn scenarios_with_rule(feature: &Feature) -> impl Iterator<Item = (Option<&Rule>, &Scenario)> {
+    feature.scenarios.iter().map(|s| (None, s)).chain(
+        feature
+            .rules
+            .iter()
+            .flat_map(|r| r.scenarios.iter().map(move |s| (Some(r), s))),
+    )
+}
+
+/// Relative to the working directory when possible, as cucumber reports it
+fn display_path(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn parse_error_data(error: &cucumber::parser::Error) -> ParseErrorRenderData {
+    match error {
+        cucumber::parser::Error::Parsing(error) => match error.as_ref() {
+            gherkin::ParseFileError::Reading { path, source } => ParseErrorRenderData {
+                path: display_path(path),
+                line: None,
+                column: None,
+                message: source.to_string(),
+            },
+            gherkin::ParseFileError::Parsing {
+                path,
+                error,
+                source,
+            } => {
+                // the position of a gherkin::ParseError is private, it is only
+                // available in its message: "Error at <line>:<column>: <expected>"
+                let message = source.to_string();
+                let position = message
+                    .strip_prefix("Error at ")
+                    .and_then(|rest| rest.split_once(": "))
+                    .and_then(|(position, _)| position.split_once(':'))
+                    .and_then(|(line, column)| Some((line.parse().ok()?, column.parse().ok()?)));
+                ParseErrorRenderData {
+                    path: display_path(path),
+                    line: position.map(|(line, _)| line),
+                    column: position.map(|(_, column)| column),
+                    message: error
+                        .as_ref()
+                        .map(|e| format!("{e} {message}"))
+                        .unwrap_or(message),
+                }
+            }
+        },
+        cucumber::parser::Error::ExampleExpansion(error) => ParseErrorRenderData {
+            path: error.path.as_deref().map(display_path).unwrap_or_default(),
+            line: Some(error.pos.line),
+            column: Some(error.pos.col),
+            message: error.to_string(),
+        },
+    }
+}
+
+/// The backgrounds that run before a scenario, the one of the feature
+/// and the one of the rule the scenario is part of
+fn backgrounds<'a>(feature: &'a Feature, scenario: &Scenario) -> Vec<&'a Background> {
+    let rule = feature
+        .rules
+        .iter()
+        .find(|r| r.scenarios.iter().any(|s| s == scenario));
+    feature
+        .background
+        .iter()
+        .chain(rule.and_then(|r| r.background.as_ref()))
+        .collect()
+}
+
+/// The scenarios cucumber expanded from the rows of an Examples table, in the
+/// order of the rows. Blank lines and comments in the table do not matter, the
+/// line of an expanded scenario only numbers the rows.
+fn expanded_rows<'a>(feature: &'a Feature, examples: &Examples) -> Vec<&'a Scenario> {
+    let mut expanded = scenarios_with_rule(feature)
+        .map(|(_, scenario)| scenario)
+        .filter(|scenario| {
+            scenario
+                .examples
+                .first()
+                .is_some_and(|ex| ex.span == examples.span)
+        })
+        .collect::<Vec<_>>();
+    expanded.sort_by_key(|scenario| scenario.position.line);
+    expanded
+}
+
+/// Sorted and without duplicates
+fn merge_tags<'a>(tags: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    tags.into_iter()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Placeholder for a feature that could not be reported
+fn feature_error_data(feature: &Feature, link: String, error: &ReporterError) -> FeatureReportData {
+    FeatureReportData {
+        stats: FeatureRenderStatsData {
+            name: feature.name.clone(),
+            link,
+            description: feature.description.clone().unwrap_or_default(),
+            tags: feature.tags.clone(),
+            all_tags: feature.tags.clone(),
+            error: Some(error.to_string()),
+            ..Default::default()
+        },
+        background: None,
+        rules: Vec::new(),
+        scenarios: Vec::new(),
+    }
+}
+
+fn write_feature_page(
+    config: &ReporterConfig,
+    templates: &Handlebars<'_>,
+    feature: &FeatureReportData,
+) -> Result<()> {
+    let data = PageRenderData {
+        title: format!("{} - {}", config.title, feature.stats.name),
+        content: render_feature(templates, feature)?,
+    };
+    let html = templates.render("page.html", &data)?;
+    write_html_file(config, html, feature.stats.link.clone())
+}
+
+fn index_data(config: &ReporterConfig, report: &ReportData) -> IndexRenderData {
+    IndexRenderData {
+        title: config.title.clone(),
+        status: report.status.clone(),
+        features: report.features.iter().map(|f| f.stats.clone()).collect(),
+        parse_errors: report.parse_errors.clone(),
+        tags: report.tags.clone(),
+        duration: report.duration.clone(),
+    }
+}
+
+fn write_index(
+    config: &ReporterConfig,
+    templates: &Handlebars<'_>,
+    report: &ReportData,
+) -> Result<()> {
+    let index_html = templates.render("index.html", &index_data(config, report))?;
+    write_html_file(config, index_html, "index.html".to_string())
+}
+
+/// The index and the feature pages as sections of one file, the links
+/// of the index point to the section named after the page of the feature
+fn write_single_file(
+    config: &ReporterConfig,
+    templates: &Handlebars<'_>,
+    report: &ReportData,
+    path: &Path,
+) -> Result<()> {
+    let anchor = |link: &str| link.trim_end_matches(".html").to_string();
+    let mut index = index_data(config, report);
+    for feature in &mut index.features {
+        feature.link = format!("#{}", anchor(&feature.link));
+    }
+    let features = report
+        .features
+        .iter()
+        .map(|feature| {
+            Ok(SingleFeatureRenderData {
+                anchor: anchor(&feature.stats.link),
+                content: render_feature(templates, feature)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let data = SingleRenderData {
+        title: config.title.clone(),
+        index,
+        features,
+    };
+    write_file(path, templates.render("single.html", &data)?)
+}
+
+fn render_feature(templates: &Handlebars<'_>, feature: &FeatureReportData) -> Result<String> {
+    let scenarios = feature
+        .scenarios
+        .iter()
+        .map(|scenario| render_scenario(templates, scenario))
+        .collect::<Result<Vec<_>>>()?;
+    let rules = feature
+        .rules
+        .iter()
+        .map(|rule| render_rule(templates, rule))
+        .collect::<Result<Vec<_>>>()?;
+
+    let data = FeatureRenderData {
+        name: feature.stats.name.clone(),
+        description: feature.stats.description.clone(),
+        tags: feature.stats.tags.clone(),
+        all_tags: feature.stats.all_tags.clone(),
+        background: feature.background.clone(),
+        scenarios: scenarios.join(""),
+        rules: rules.join(""),
+        duration: feature.stats.duration.clone(),
+        error: feature.stats.error.clone(),
+    };
+    Ok(templates.render("feature.html", &data)?)
+}
+
+fn render_rule(templates: &Handlebars<'_>, rule: &RuleReportData) -> Result<String> {
+    let scenarios = rule
+        .scenarios
+        .iter()
+        .map(|scenario| render_scenario(templates, scenario))
+        .collect::<Result<Vec<_>>>()?;
+    let data = RuleRenderData {
+        name: rule.name.clone(),
+        description: rule.description.clone(),
+        tags: rule.tags.clone(),
+        background: rule.background.clone(),
+        scenarios: scenarios.join(""),
+        duration: rule.duration.clone(),
+    };
+    Ok(templates.render("rule.html", &data)?)
+}
+
+fn render_scenario(templates: &Handlebars<'_>, scenario: &ScenarioReportData) -> Result<String> {
+    let html = match scenario {
+        ScenarioReportData::Scenario(data) => templates.render("scenario.html", data)?,
+        ScenarioReportData::Outline(data) => templates.render("outline.html", data)?,
+    };
+    Ok(html)
+}
+
+fn write_html_file(config: &ReporterConfig, html: String, filename: String) -> Result<()> {
+    let filename = if let Some(path) = &config.output_dir {
+        std::fs::create_dir_all(path).map_err(ReporterError::io(path))?;
+        path.join(filename)
     } else {
-        format!("{}", filename)
+        PathBuf::from(filename)
     };
-    std::fs::write(&filename, &html)?;
-    Ok(())
+    std::fs::write(&filename, &html).map_err(ReporterError::io(&filename))
+}
+
+fn write_file(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(ReporterError::io(parent))?;
+    }
+    std::fs::write(path, content).map_err(ReporterError::io(path))
 }
 
 #[derive(Args)]
 pub struct ReporterArgs {
     #[arg(long = "output-html-path")]
     pub output_html_path: Option<String>,
+    /// Also write a JUnit XML report to this file
+    #[arg(long = "output-junit-path")]
+    pub output_junit_path: Option<String>,
+    /// Also write the collected results as report.json next to index.html
+    #[arg(long = "output-json")]
+    pub output_json: bool,
+    /// Also write the run as cucumber messages (ndjson) to this file
+    #[arg(long = "output-messages-path")]
+    pub output_messages_path: Option<String>,
+    /// Also write the index and every feature page to this one html file
+    #[arg(long = "output-single-html-path")]
+    pub output_single_html_path: Option<String>,
+    /// Also write a compact markdown summary to this file, e.g. $GITHUB_STEP_SUMMARY
+    #[arg(long = "output-markdown-path")]
+    pub output_markdown_path: Option<String>,
+    /// Also write Allure result files to this directory
+    #[arg(long = "output-allure-path")]
+    pub output_allure_path: Option<String>,
+    /// Also write a CTRF json report to this file
+    #[arg(long = "output-ctrf-path")]
+    pub output_ctrf_path: Option<String>,
+    /// Also write the report as a single pdf to this file
+    #[arg(long = "output-pdf-path")]
+    pub output_pdf_path: Option<String>,
+    /// Directory with templates replacing the embedded ones
+    #[arg(long = "template-dir")]
+    pub template_dir: Option<String>,
+    /// Title shown on the report pages
+    #[arg(long = "report-title")]
+    pub report_title: Option<String>,
+    /// Render descriptions as markdown
+    #[arg(long = "markdown-descriptions")]
+    pub markdown_descriptions: bool,
+    /// Name the feature pages `F<hash>.html`, as earlier versions did
+    #[arg(long = "hashed-filenames")]
+    pub hashed_filenames: bool,
 }
 
 impl Normalized for CucumberReporter {}
@@ -430,26 +1342,60 @@ where
         ev: cucumber::parser::Result<cucumber::Event<cucumber::event::Cucumber<W>>>,
         cli: &Self::Cli,
     ) {
-        if let Ok(Event { value, .. }) = ev {
-            match value {
-                Feature(gherkin_feature, event) => {
-                    self.add_feature(gherkin_feature.into());
-                    match event {
-                        event::Feature::Rule(_rule, event) => {
-                            self.nr_rules += 1;
-                            if let event::Rule::Scenario(_, event) = event {
-                                self.process_scenario(event)
+        // at the first event, parse errors are reported before the run is started
+        let config = self
+            .config
+            .get_or_insert_with(|| self.settings.resolve(cli));
+        let messages_path = (config.messages_path.clone()).filter(|_| !self.messages_failed);
+        let Event { value, at, .. } = match ev {
+            Ok(event) => event,
+            Err(error) => {
+                let error = parse_error_data(&error);
+                if let Some(path) = messages_path {
+                    let written = self.write_parse_error_message(&path, &error);
+                    self.messages_written(written);
+                }
+                self.parse_errors.push(error);
+                return;
+            }
+        };
+        if let Some(path) = messages_path {
+            let written = self.write_messages(&path, &value, at);
+            self.messages_written(written);
+        }
+        match value {
+            Started => self.start_timer(Timed::Run, at),
+            Feature(gherkin_feature, event) => {
+                let feature: Arc<Feature> = gherkin_feature.into();
+                self.add_feature(feature.clone());
+                match event {
+                    event::Feature::Started => self.start_timer(Timed::Feature(feature.id()), at),
+                    event::Feature::Finished => self.stop_timer(Timed::Feature(feature.id()), at),
+                    event::Feature::Rule(rule, event) => {
+                        let timed = Timed::Rule(feature.id(), rule.id());
+                        match event {
+                            event::Rule::Started => self.start_timer(timed, at),
+                            event::Rule::Finished => self.stop_timer(timed, at),
+                            event::Rule::Scenario(scenario, event) => {
+                                self.process_scenario(&feature, &scenario, event, at)
                             }
                         }
-                        event::Feature::Scenario(_, event) => self.process_scenario(event),
-                        _ => {}
+                    }
+                    event::Feature::Scenario(scenario, event) => {
+                        self.process_scenario(&feature, &scenario, event, at)
                     }
                 }
-                cucumber::event::Cucumber::Finished => {
-                    self.finish(cli).await.expect("Failed to finish reporter");
-                }
-                _ => {}
             }
-        };
+            cucumber::event::Cucumber::Finished => {
+                self.stop_timer(Timed::Run, at);
+                let config = self
+                    .config
+                    .take()
+                    .unwrap_or_else(|| self.settings.resolve(cli));
+                self.finish(config).await;
+                self.print_problems();
+            }
+            _ => {}
+        }
     }
 }
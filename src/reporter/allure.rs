@@ -0,0 +1,231 @@
+use serde::Serialize;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use super::{Result, write_file};
+use crate::render_types::*;
+
+/// Writes an [Allure](https://allurereport.org/docs/how-it-works-test-result-file/)
+/// result file per scenario and per example row, with a container file for its hooks
+pub(super) fn write_allure_results(dir: &Path, report: &ReportData) -> Result<()> {
+    let run = report
+        .duration
+        .as_ref()
+        .and_then(|d| d.start)
+        .unwrap_or_default();
+    // scenarios can share a name, the uuid is unique by their order
+    let mut id = 0;
+    for feature in &report.features {
+        for (rule, test) in feature.scenario_runs() {
+            let rule = rule.map(|rule| rule.name.as_str());
+            let full_name = match rule {
+                Some(rule) => format!("{}: {rule}: {}", feature.stats.name, test.name),
+                None => format!("{}: {}", feature.stats.name, test.name),
+            };
+            let history_id = hex_hash(&(&full_name, &test.parameters));
+            id += 1;
+            let uuid = allure_uuid(&(&history_id, run, id));
+
+            let container = AllureContainer {
+                uuid: allure_uuid(&(&uuid, "container")),
+                name: full_name.clone(),
+                children: vec![uuid.clone()],
+                befores: test.before_hooks.iter().map(step).collect(),
+                afters: test.after_hooks.iter().map(step).collect(),
+                start: test.duration.and_then(|d| d.start),
+                stop: test.duration.and_then(|d| d.stop()),
+            };
+
+            let steps = test.background.iter().chain(test.steps).collect::<Vec<_>>();
+            let state = test.state();
+            let failed_step = test
+                .all_steps()
+                .into_iter()
+                .find(|step| step.step_state != StepState::Passed);
+
+            let result = AllureResult {
+                uuid: uuid.clone(),
+                history_id,
+                test_case_id: hex_hash(&full_name),
+                name: test.name.to_string(),
+                full_name,
+                status: status(&state),
+                status_details: StatusDetails {
+                    flaky: test.flaky,
+                    ..failed_step.as_ref().map(status_details).unwrap_or_default()
+                },
+                stage: "finished",
+                description: test.description.to_string(),
+                labels: [
+                    ("feature", Some(feature.stats.name.clone())),
+                    ("suite", Some(feature.stats.name.clone())),
+                    ("story", rule.map(str::to_string)),
+                    ("framework", Some("cucumber-rs".to_string())),
+                    ("language", Some("rust".to_string())),
+                ]
+                .into_iter()
+                .filter_map(|(name, value)| value.map(|value| Label { name, value }))
+                .chain(test.tags.iter().map(|tag| Label {
+                    name: "tag",
+                    value: tag.clone(),
+                }))
+                .collect(),
+                parameters: test
+                    .parameters
+                    .iter()
+                    .map(|(name, value)| Parameter {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                steps: steps.into_iter().map(step).collect(),
+                start: test.duration.and_then(|d| d.start),
+                stop: test.duration.and_then(|d| d.stop()),
+            };
+
+            write_file(
+                &dir.join(format!("{}-result.json", result.uuid)),
+                serde_json::to_string(&result)?,
+            )?;
+            write_file(
+                &dir.join(format!("{}-container.json", container.uuid)),
+                serde_json::to_string(&container)?,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn status(state: &StepState) -> &'static str {
+    match state {
+        StepState::Passed => "passed",
+        StepState::Failed => "failed",
+        StepState::Ambiguous => "broken",
+        StepState::Undefined | StepState::Skipped | StepState::NotRun => "skipped",
+    }
+}
+
+fn status_details(step: &StepRenderData) -> StatusDetails {
+    let message = match (&step.step_error, &step.step_state) {
+        (Some(error), _) => Some(error.message.clone()),
+        (None, StepState::Undefined) => Some(format!(
+            "Undefined step: {} {}",
+            step.keyword, step.step_template
+        )),
+        _ => None,
+    };
+    StatusDetails {
+        message,
+        trace: step
+            .step_error
+            .as_ref()
+            .and_then(|error| error.payload.clone()),
+        flaky: false,
+    }
+}
+
+fn step(step: &StepRenderData) -> AllureStep {
+    AllureStep {
+        name: format!("{} {}", step.keyword, step.step_template),
+        status: status(&step.step_state),
+        status_details: status_details(step),
+        stage: "finished",
+        parameters: Vec::new(),
+        steps: Vec::new(),
+        start: step.duration.as_ref().and_then(|d| d.start),
+        stop: step.duration.as_ref().and_then(|d| d.stop()),
+    }
+}
+
+fn hex_hash(value: &impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Allure wants a uuid, it only has to be unique within the results directory
+fn allure_uuid(value: &impl Hash) -> String {
+    let high = hex_hash(value);
+    let low = hex_hash(&(value, &high));
+    format!(
+        "{}-{}-{}-{}-{}",
+        &high[..8],
+        &high[8..12],
+        &high[12..16],
+        &low[..4],
+        &low[4..16]
+    )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AllureResult {
+    uuid: String,
+    history_id: String,
+    test_case_id: String,
+    name: String,
+    full_name: String,
+    status: &'static str,
+    status_details: StatusDetails,
+    stage: &'static str,
+    description: String,
+    labels: Vec<Label>,
+    parameters: Vec<Parameter>,
+    steps: Vec<AllureStep>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AllureContainer {
+    uuid: String,
+    name: String,
+    children: Vec<String>,
+    befores: Vec<AllureStep>,
+    afters: Vec<AllureStep>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AllureStep {
+    name: String,
+    status: &'static str,
+    status_details: StatusDetails,
+    stage: &'static str,
+    parameters: Vec<Parameter>,
+    steps: Vec<AllureStep>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<u64>,
+}
+
+#[derive(Serialize, Default)]
+struct StatusDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<String>,
+    flaky: bool,
+}
+
+#[derive(Serialize)]
+struct Label {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Parameter {
+    name: String,
+    value: String,
+}
@@ -0,0 +1,339 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use super::{CucumberReporter, ReporterArgs};
+
+/// The reports the reporter can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportFormat {
+    /// An html page per feature and an index.html
+    Html,
+    /// report.json next to index.html
+    Json,
+    /// A JUnit XML report, junit.xml in the output directory unless a path is given
+    Junit,
+    /// Cucumber messages (ndjson), messages.ndjson in the output directory unless a path is given
+    Messages,
+    /// A single pdf with the index and every feature, report.pdf in the output directory unless a path is given
+    Pdf,
+    /// The index and every feature page in one html file, report.html in the output directory unless a path is given
+    SingleHtml,
+    /// A compact markdown summary, summary.md in the output directory unless a path is given
+    Markdown,
+    /// Allure result files, in allure-results in the output directory unless a path is given
+    Allure,
+    /// A CTRF json report, ctrf.json in the output directory unless a path is given
+    Ctrf,
+}
+
+/// How the html page of a feature is named
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameStrategy {
+    /// Derived from the path of the feature file relative to the working directory,
+    /// or the feature name when there is no path, e.g. `features_login.html`.
+    /// A number is added when two features end up with the same name.
+    #[default]
+    Slug,
+    /// `F<hash of the feature name>.html`, the names used by earlier versions
+    Hashed,
+}
+
+/// Configuration set in code, see [`CucumberReporter::builder`]
+#[derive(Debug, Clone)]
+pub(super) struct ReporterSettings {
+    output_dir: Option<PathBuf>,
+    title: Option<String>,
+    pub(super) template_dir: Option<PathBuf>,
+    formats: HashSet<ReportFormat>,
+    junit_path: Option<PathBuf>,
+    messages_path: Option<PathBuf>,
+    pdf_path: Option<PathBuf>,
+    single_html_path: Option<PathBuf>,
+    markdown_path: Option<PathBuf>,
+    allure_dir: Option<PathBuf>,
+    ctrf_path: Option<PathBuf>,
+    filename_strategy: FilenameStrategy,
+    markdown_descriptions: bool,
+}
+
+impl Default for ReporterSettings {
+    fn default() -> Self {
+        Self {
+            output_dir: None,
+            title: None,
+            template_dir: None,
+            formats: HashSet::from([ReportFormat::Html]),
+            junit_path: None,
+            messages_path: None,
+            pdf_path: None,
+            single_html_path: None,
+            markdown_path: None,
+            allure_dir: None,
+            ctrf_path: None,
+            filename_strategy: FilenameStrategy::default(),
+            markdown_descriptions: false,
+        }
+    }
+}
+
+/// The settings merged with the commandline options
+#[derive(Debug, Clone)]
+pub(super) struct ReporterConfig {
+    pub output_dir: Option<PathBuf>,
+    pub title: String,
+    pub template_dir: Option<PathBuf>,
+    pub html: bool,
+    pub json: bool,
+    pub junit_path: Option<PathBuf>,
+    pub messages_path: Option<PathBuf>,
+    pub pdf_path: Option<PathBuf>,
+    pub single_html_path: Option<PathBuf>,
+    pub markdown_path: Option<PathBuf>,
+    pub allure_dir: Option<PathBuf>,
+    pub ctrf_path: Option<PathBuf>,
+    pub filename_strategy: FilenameStrategy,
+    pub markdown_descriptions: bool,
+}
+
+impl ReporterSettings {
+    /// Commandline options win over the settings made in code,
+    /// a format is enabled when either one enables it.
+    pub(super) fn resolve(&self, args: &ReporterArgs) -> ReporterConfig {
+        let output_dir = args
+            .output_html_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.output_dir.clone());
+        let in_output_dir = |filename: &str| {
+            output_dir
+                .as_ref()
+                .map(|dir| dir.join(filename))
+                .unwrap_or_else(|| PathBuf::from(filename))
+        };
+
+        let junit_path = args
+            .output_junit_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.junit_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Junit)
+                    .then(|| in_output_dir("junit.xml"))
+            });
+        let messages_path = args
+            .output_messages_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.messages_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Messages)
+                    .then(|| in_output_dir("messages.ndjson"))
+            });
+        let pdf_path = args
+            .output_pdf_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.pdf_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Pdf)
+                    .then(|| in_output_dir("report.pdf"))
+            });
+        let single_html_path = args
+            .output_single_html_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.single_html_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::SingleHtml)
+                    .then(|| in_output_dir("report.html"))
+            });
+        let markdown_path = args
+            .output_markdown_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.markdown_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Markdown)
+                    .then(|| in_output_dir("summary.md"))
+            });
+        let allure_dir = args
+            .output_allure_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.allure_dir.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Allure)
+                    .then(|| in_output_dir("allure-results"))
+            });
+        let ctrf_path = args
+            .output_ctrf_path
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(|| self.ctrf_path.clone())
+            .or_else(|| {
+                self.formats
+                    .contains(&ReportFormat::Ctrf)
+                    .then(|| in_output_dir("ctrf.json"))
+            });
+
+        ReporterConfig {
+            title: args
+                .report_title
+                .clone()
+                .or_else(|| self.title.clone())
+                .unwrap_or_else(|| "Cucumber report".to_string()),
+            template_dir: args
+                .template_dir
+                .as_ref()
+                .map(PathBuf::from)
+                .or_else(|| self.template_dir.clone()),
+            html: args.output_html_path.is_some() || self.formats.contains(&ReportFormat::Html),
+            json: args.output_json || self.formats.contains(&ReportFormat::Json),
+            junit_path,
+            messages_path,
+            pdf_path,
+            single_html_path,
+            markdown_path,
+            allure_dir,
+            ctrf_path,
+            filename_strategy: if args.hashed_filenames {
+                FilenameStrategy::Hashed
+            } else {
+                self.filename_strategy
+            },
+            markdown_descriptions: args.markdown_descriptions || self.markdown_descriptions,
+            output_dir,
+        }
+    }
+}
+
+/// Builds a [`CucumberReporter`] configured in code instead of on the commandline.
+///
+/// The commandline options still apply and take precedence over the values
+/// set here. Formats enabled on the commandline are added to the formats set here.
+/// ```rust
+///   use cucumber::{World, WriterExt, writer::Basic};
+///   use cucumber_reporter::{CucumberReporter, ReportFormat};
+///
+///   #[derive(World,Debug,Default)]
+///   struct MyWorld;
+///
+///   MyWorld::cucumber()
+///        .with_default_cli()
+///        .with_writer(
+///           Basic::stdout()
+///                .summarized()
+///                .tee::<MyWorld, _>(
+///                    CucumberReporter::builder()
+///                        .output_dir("target/report")
+///                        .title("Acceptance tests")
+///                        .formats([ReportFormat::Html, ReportFormat::Junit])
+///                        .build(),
+///                ),
+///        )
+///        .run("features");
+/// ```
+#[derive(Debug, Default)]
+pub struct CucumberReporterBuilder {
+    settings: ReporterSettings,
+}
+
+impl CucumberReporterBuilder {
+    /// Directory the html pages and other reports are written to,
+    /// `--output-html-path` on the commandline.
+    pub fn output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.settings.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// Title of the report, `--report-title` on the commandline.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.settings.title = Some(title.into());
+        self
+    }
+
+    /// Directory with templates replacing the embedded ones, `--template-dir` on the commandline.
+    pub fn template_dir(mut self, template_dir: impl Into<PathBuf>) -> Self {
+        self.settings.template_dir = Some(template_dir.into());
+        self
+    }
+
+    /// The reports to produce, only [`ReportFormat::Html`] by default.
+    ///
+    /// A report is also produced when its path is given, with the builder or on
+    /// the commandline, even when its format is not in `formats`. E.g.
+    /// `--output-junit-path` writes the JUnit report for `formats([ReportFormat::Html])`.
+    pub fn formats(mut self, formats: impl IntoIterator<Item = ReportFormat>) -> Self {
+        self.settings.formats = formats.into_iter().collect();
+        self
+    }
+
+    /// Writes the JUnit report to this file, `--output-junit-path` on the commandline.
+    pub fn junit_path(mut self, junit_path: impl Into<PathBuf>) -> Self {
+        self.settings.junit_path = Some(junit_path.into());
+        self
+    }
+
+    /// Writes the cucumber messages to this file, `--output-messages-path` on the commandline.
+    pub fn messages_path(mut self, messages_path: impl Into<PathBuf>) -> Self {
+        self.settings.messages_path = Some(messages_path.into());
+        self
+    }
+
+    /// Writes the report as a single pdf to this file, `--output-pdf-path` on the commandline.
+    pub fn pdf_path(mut self, pdf_path: impl Into<PathBuf>) -> Self {
+        self.settings.pdf_path = Some(pdf_path.into());
+        self
+    }
+
+    /// Writes the index and every feature page to this one html file,
+    /// `--output-single-html-path` on the commandline.
+    pub fn single_html_path(mut self, single_html_path: impl Into<PathBuf>) -> Self {
+        self.settings.single_html_path = Some(single_html_path.into());
+        self
+    }
+
+    /// Writes a compact markdown summary to this file, e.g. `$GITHUB_STEP_SUMMARY`,
+    /// `--output-markdown-path` on the commandline.
+    pub fn markdown_path(mut self, markdown_path: impl Into<PathBuf>) -> Self {
+        self.settings.markdown_path = Some(markdown_path.into());
+        self
+    }
+
+    /// Writes the Allure result files to this directory, `--output-allure-path` on the commandline.
+    pub fn allure_dir(mut self, allure_dir: impl Into<PathBuf>) -> Self {
+        self.settings.allure_dir = Some(allure_dir.into());
+        self
+    }
+
+    /// Writes a CTRF json report to this file, `--output-ctrf-path` on the commandline.
+    pub fn ctrf_path(mut self, ctrf_path: impl Into<PathBuf>) -> Self {
+        self.settings.ctrf_path = Some(ctrf_path.into());
+        self
+    }
+
+    /// How the feature pages are named, [`FilenameStrategy::Slug`] by default,
+    /// `--hashed-filenames` on the commandline selects [`FilenameStrategy::Hashed`].
+    pub fn filename_strategy(mut self, filename_strategy: FilenameStrategy) -> Self {
+        self.settings.filename_strategy = filename_strategy;
+        self
+    }
+
+    /// Renders the descriptions of features, rules, scenarios and examples as markdown,
+    /// `--markdown-descriptions` on the commandline.
+    pub fn markdown_descriptions(mut self, markdown_descriptions: bool) -> Self {
+        self.settings.markdown_descriptions = markdown_descriptions;
+        self
+    }
+
+    pub fn build(self) -> CucumberReporter {
+        let mut reporter = CucumberReporter::new();
+        reporter.settings = self.settings;
+        reporter
+    }
+}
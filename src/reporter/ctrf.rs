@@ -0,0 +1,199 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::{ReporterConfig, Result, index_data};
+use crate::render_types::*;
+
+/// Renders the run as a [CTRF](https://ctrf.io/docs/specification/overview) report,
+/// a test for every scenario and every example row, with the counts of the index
+pub(super) fn ctrf_render(config: &ReporterConfig, report: &ReportData) -> Result<String> {
+    let index = index_data(config, report);
+    let stats = &index.features;
+    let sum = |count: fn(&FeatureRenderStatsData) -> usize| stats.iter().map(count).sum();
+
+    let tests = report
+        .features
+        .iter()
+        .flat_map(|feature| {
+            feature.scenario_runs().into_iter().map(|(rule, run)| {
+                let suite = match rule {
+                    Some(rule) => format!("{} > {}", feature.stats.name, rule.name),
+                    None => feature.stats.name.clone(),
+                };
+                test(suite, &run)
+            })
+        })
+        .collect::<Vec<_>>();
+    let count = |status: &str| tests.iter().filter(|test| test.status == status).count();
+
+    let start = index.duration.as_ref().and_then(|d| d.start);
+    let ctrf = Ctrf {
+        report_format: "CTRF",
+        spec_version: "0.0.0",
+        results: CtrfResults {
+            tool: Tool {
+                name: "cucumber-rs",
+            },
+            summary: Summary {
+                tests: tests.len(),
+                passed: count("passed"),
+                failed: count("failed"),
+                pending: count("pending"),
+                skipped: count("skipped"),
+                other: count("other"),
+                start: start.unwrap_or_default(),
+                stop: index
+                    .duration
+                    .as_ref()
+                    .and_then(|d| d.stop())
+                    .unwrap_or_default(),
+                extra: SummaryExtra {
+                    title: index.title.clone(),
+                    status: index.status.clone(),
+                    nr_features: stats.len(),
+                    nr_rules: sum(|f| f.nr_rules),
+                    nr_scenarios: sum(|f| f.nr_scenarios),
+                    nr_steps: sum(|f| f.nr_steps),
+                    nr_errors: sum(|f| f.nr_errors),
+                    nr_undefined: sum(|f| f.nr_undefined),
+                    nr_ambiguous: sum(|f| f.nr_ambiguous),
+                    nr_background_errors: sum(|f| f.nr_background_errors),
+                    nr_flaky: sum(|f| f.nr_flaky),
+                    nr_skipped: sum(|f| f.nr_skipped),
+                    nr_not_run: sum(|f| f.nr_not_run),
+                    nr_parse_errors: index.parse_errors.len(),
+                },
+            },
+            tests,
+        },
+    };
+    Ok(serde_json::to_string_pretty(&ctrf)?)
+}
+
+fn test(suite: String, run: &ScenarioRun<'_>) -> Test {
+    let steps = run.all_steps();
+    let failed_step = steps
+        .iter()
+        .find(|step| step.step_state != StepState::Passed);
+    let message = failed_step.and_then(|step| match &step.step_error {
+        Some(error) => Some(error.message.clone()),
+        None if step.step_state == StepState::Undefined => Some(format!(
+            "Undefined step: {} {}",
+            step.keyword, step.step_template
+        )),
+        None => None,
+    });
+    Test {
+        name: run.display_name(),
+        status: status(&run.state()),
+        duration: run.duration.map(|d| d.millis).unwrap_or_default(),
+        start: run.duration.and_then(|d| d.start),
+        stop: run.duration.and_then(|d| d.stop()),
+        message,
+        trace: failed_step
+            .and_then(|step| step.step_error.as_ref())
+            .and_then(|error| error.payload.clone()),
+        suite,
+        tags: run.tags.to_vec(),
+        flaky: run.flaky,
+        retries: run.attempts.len().saturating_sub(1),
+        parameters: run.parameters.iter().cloned().collect(),
+        steps: steps
+            .iter()
+            .map(|step| Step {
+                name: format!("{} {}", step.keyword, step.step_template),
+                status: status(&step.step_state),
+            })
+            .collect(),
+    }
+}
+
+fn status(state: &StepState) -> &'static str {
+    match state {
+        StepState::Passed => "passed",
+        StepState::Failed | StepState::Ambiguous => "failed",
+        StepState::Undefined => "pending",
+        StepState::Skipped | StepState::NotRun => "skipped",
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Ctrf {
+    report_format: &'static str,
+    spec_version: &'static str,
+    results: CtrfResults,
+}
+
+#[derive(Serialize)]
+struct CtrfResults {
+    tool: Tool,
+    summary: Summary,
+    tests: Vec<Test>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    name: &'static str,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    tests: usize,
+    passed: usize,
+    failed: usize,
+    pending: usize,
+    skipped: usize,
+    other: usize,
+    start: u64,
+    stop: u64,
+    extra: SummaryExtra,
+}
+
+/// The counts shown on the index, CTRF only counts tests
+#[derive(Serialize)]
+struct SummaryExtra {
+    title: String,
+    status: StepState,
+    nr_features: usize,
+    nr_rules: usize,
+    nr_scenarios: usize,
+    nr_steps: usize,
+    nr_errors: usize,
+    nr_undefined: usize,
+    nr_ambiguous: usize,
+    nr_background_errors: usize,
+    nr_flaky: usize,
+    nr_skipped: usize,
+    nr_not_run: usize,
+    nr_parse_errors: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Test {
+    name: String,
+    status: &'static str,
+    duration: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<String>,
+    suite: String,
+    tags: Vec<String>,
+    flaky: bool,
+    retries: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    parameters: BTreeMap<String, String>,
+    steps: Vec<Step>,
+}
+
+#[derive(Serialize)]
+struct Step {
+    name: String,
+    status: &'static str,
+}
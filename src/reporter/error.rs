@@ -0,0 +1,125 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Problems while writing the reports. They do not stop the run, they are
+/// collected and listed on stderr when the run is finished.
+#[derive(Debug)]
+pub(crate) enum ReporterError {
+    /// Writing a report file failed
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The template directory could not be read
+    TemplateDir {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A file in the template directory that does not replace an embedded template
+    UnknownTemplate {
+        path: PathBuf,
+        templates: Vec<String>,
+    },
+    /// A template could not be registered
+    Template(Box<handlebars::TemplateError>),
+    /// A template could not be rendered
+    Render(Box<handlebars::RenderError>),
+    Json(serde_json::Error),
+    /// The outline of an expanded scenario could not be put together again
+    OutlineNotFound {
+        feature: String,
+        scenario: String,
+    },
+    /// No scenario was run for an example row
+    ExampleNotFound {
+        feature: String,
+        scenario: String,
+        examples_line: usize,
+        row: usize,
+    },
+    /// Examples without a table or without a header row
+    ExamplesWithoutTable {
+        feature: String,
+        scenario: String,
+    },
+}
+
+impl ReporterError {
+    pub(crate) fn io(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+        move |source| ReporterError::Io { path, source }
+    }
+}
+
+impl fmt::Display for ReporterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReporterError::Io { path, source } => {
+                write!(f, "could not write {}: {source}", path.display())
+            }
+            ReporterError::TemplateDir { path, source } => {
+                write!(
+                    f,
+                    "could not read template directory {}: {source}",
+                    path.display()
+                )
+            }
+            ReporterError::UnknownTemplate { path, templates } => write!(
+                f,
+                "{} is not used, the templates are: {}",
+                path.display(),
+                templates.join(", ")
+            ),
+            ReporterError::Template(error) => write!(f, "invalid template: {error}"),
+            ReporterError::Render(error) => write!(f, "could not render template: {error}"),
+            ReporterError::Json(error) => write!(f, "could not write json: {error}"),
+            ReporterError::OutlineNotFound { feature, scenario } => write!(
+                f,
+                "outline of scenario '{scenario}' not found in feature '{feature}'"
+            ),
+            ReporterError::ExampleNotFound {
+                feature,
+                scenario,
+                examples_line,
+                row,
+            } => write!(
+                f,
+                "no results for row {row} of the examples on line {examples_line} of outline '{scenario}' in feature '{feature}'"
+            ),
+            ReporterError::ExamplesWithoutTable { feature, scenario } => write!(
+                f,
+                "examples without a table in outline '{scenario}' of feature '{feature}'"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReporterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReporterError::Io { source, .. } => Some(source),
+            ReporterError::TemplateDir { source, .. } => Some(source),
+            ReporterError::Template(error) => Some(error.as_ref()),
+            ReporterError::Render(error) => Some(error.as_ref()),
+            ReporterError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<handlebars::TemplateError> for ReporterError {
+    fn from(error: handlebars::TemplateError) -> Self {
+        ReporterError::Template(Box::new(error))
+    }
+}
+
+impl From<handlebars::RenderError> for ReporterError {
+    fn from(error: handlebars::RenderError) -> Self {
+        ReporterError::Render(Box::new(error))
+    }
+}
+
+impl From<serde_json::Error> for ReporterError {
+    fn from(error: serde_json::Error) -> Self {
+        ReporterError::Json(error)
+    }
+}
@@ -0,0 +1,90 @@
+use handlebars::Handlebars;
+
+use super::Result;
+use crate::render_types::*;
+
+/// Renders the run as a JUnit XML report, a testsuite per feature and a
+/// testcase for every scenario and every example row
+pub(super) fn junit_render(templates: &Handlebars<'_>, report: &ReportData) -> Result<String> {
+    let suites = report.features.iter().map(suite).collect::<Vec<_>>();
+    let data = JunitRenderData {
+        tests: suites.iter().map(|s| s.tests).sum(),
+        failures: suites.iter().map(|s| s.failures).sum(),
+        skipped: suites.iter().map(|s| s.skipped).sum(),
+        time: seconds(report.duration.as_ref()),
+        suites,
+    };
+    Ok(templates.render("junit.xml", &data)?)
+}
+
+fn suite(feature: &FeatureReportData) -> JunitSuiteRenderData {
+    let cases = feature
+        .scenario_runs()
+        .into_iter()
+        .map(|(rule, run)| {
+            let classname = match rule {
+                Some(rule) => format!("{} - {}", feature.stats.name, rule.name),
+                None => feature.stats.name.clone(),
+            };
+            case(classname, &run)
+        })
+        .collect::<Vec<_>>();
+
+    JunitSuiteRenderData {
+        name: xml_text(&feature.stats.name),
+        tests: cases.len(),
+        failures: cases.iter().filter(|c| c.state.is_failure()).count(),
+        skipped: cases.iter().filter(|c| c.skipped).count(),
+        time: seconds(feature.stats.duration.as_ref()),
+        cases,
+    }
+}
+
+fn case(classname: String, run: &ScenarioRun<'_>) -> JunitCaseRenderData {
+    let state = run.state();
+    let failure = run
+        .all_steps()
+        .into_iter()
+        .find(|step| step.step_state.is_failure())
+        .map(|step| {
+            let error = step.step_error.as_ref();
+            let step_line = format!("{} {}", step.keyword, step.step_template);
+            JunitFailureRenderData {
+                kind: error.map(|e| e.kind.clone()),
+                message: xml_text(
+                    &error
+                        .map(|e| e.message.clone())
+                        .unwrap_or_else(|| "Step failed".to_string()),
+                ),
+                details: xml_text(&match error.and_then(|e| e.payload.as_ref()) {
+                    Some(payload) => format!("{step_line}\n{payload}"),
+                    None => step_line,
+                }),
+            }
+        });
+
+    JunitCaseRenderData {
+        name: xml_text(&run.display_name()),
+        classname: xml_text(&classname),
+        time: seconds(run.duration),
+        skipped: !state.is_failure() && state != StepState::Passed,
+        state,
+        failure,
+    }
+}
+
+fn seconds(duration: Option<&DurationRenderData>) -> f64 {
+    duration
+        .map(|d| d.millis as f64 / 1000.0)
+        .unwrap_or_default()
+}
+
+/// Without the characters XML 1.0 does not allow, e.g. the escape codes of
+/// colored output in a panic message. Escaping is left to the template.
+fn xml_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| {
+            matches!(c, '\t' | '\n' | '\r') || (*c >= ' ' && !matches!(c, '\u{fffe}' | '\u{ffff}'))
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, html_escape,
+};
+use pulldown_cmark::{Parser, html};
+
+/// `{{description value}}` renders a description of a feature, rule, scenario
+/// or example. As markdown when enabled, otherwise as escaped plain text.
+pub(super) struct DescriptionHelper {
+    pub markdown: bool,
+}
+
+impl HelperDef for DescriptionHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let description = h
+            .param(0)
+            .and_then(|p| p.value().as_str())
+            .unwrap_or_default();
+        if self.markdown {
+            out.write(&markdown_to_html(description))?;
+        } else {
+            out.write(&html_escape(description))?;
+        }
+        Ok(())
+    }
+}
+
+/// Feature files can come from anywhere, so the html is sanitized
+fn markdown_to_html(markdown: &str) -> String {
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new(markdown));
+    ammonia::clean(&unsafe_html)
+}
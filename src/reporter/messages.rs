@@ -0,0 +1,1196 @@
+use cucumber::event::{self, HookType, StepError};
+use gherkin::{
+    Background, Examples, Feature, LineCol, Rule, Scenario, Span, Step, StepType, Table,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    CucumberReporter, ReporterError, Result, ToId, backgrounds, display_path, scenarios_with_rule,
+};
+use crate::render_types::{DocStringRenderData, ParseErrorRenderData, panic_payload};
+
+/// Writes the run as [cucumber messages](https://github.com/cucumber/messages),
+/// one json envelope per line, so the standard cucumber tooling can read it.
+/// The sources, documents and pickles are written as the features are parsed,
+/// the test run when it is finished, as the tooling expects every pickle
+/// before the test run starts.
+#[derive(Debug)]
+pub(super) struct Messages {
+    path: PathBuf,
+    out: BufWriter<File>,
+    /// The envelopes of the test run, from `testRunStarted` on
+    run: Vec<String>,
+    /// Test steps started and finished per started test case
+    test_cases: HashMap<String, TestCaseProgress>,
+    /// Ids of the test cases and hooks already written
+    written: HashSet<String>,
+    success: bool,
+}
+
+#[derive(Debug, Default)]
+struct TestCaseProgress {
+    /// Written when the test case finished, a test case lists the hooks
+    /// that ran for it and those are only known by then
+    envelopes: Vec<String>,
+    hooks: Vec<HookType>,
+    started: HashMap<String, SystemTime>,
+    finished: HashSet<String>,
+    failed: bool,
+}
+
+impl TestCaseProgress {
+    fn buffer(&mut self, envelope: Envelope) -> Result<()> {
+        self.envelopes.push(serde_json::to_string(&envelope)?);
+        Ok(())
+    }
+}
+
+impl Messages {
+    /// Starts with the meta envelope, cucumber reports parse errors before
+    /// the run is started
+    fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(ReporterError::io(parent))?;
+        }
+        let mut messages = Self {
+            path: path.to_path_buf(),
+            out: BufWriter::new(File::create(path).map_err(ReporterError::io(path))?),
+            run: Vec::new(),
+            test_cases: HashMap::new(),
+            written: HashSet::new(),
+            success: true,
+        };
+        messages.write(Envelope::Meta(Meta::new()))?;
+        Ok(messages)
+    }
+
+    fn write(&mut self, envelope: Envelope) -> Result<()> {
+        let line = serde_json::to_string(&envelope)?;
+        self.write_line(&line)
+    }
+
+    fn write_run(&mut self, envelope: Envelope) -> Result<()> {
+        self.run.push(serde_json::to_string(&envelope)?);
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<()> {
+        writeln!(self.out, "{line}").map_err(ReporterError::io(&self.path))
+    }
+
+    /// Writes the test run after everything written before
+    fn finish(&mut self) -> Result<()> {
+        for line in std::mem::take(&mut self.run) {
+            self.write_line(&line)?;
+        }
+        self.out.flush().map_err(ReporterError::io(&self.path))
+    }
+}
+
+impl CucumberReporter {
+    /// One problem is enough, the messages are incomplete from there on
+    pub(super) fn messages_written(&mut self, written: Result<()>) {
+        if let Err(error) = written {
+            self.messages_failed = true;
+            self.problems.push(error);
+        }
+    }
+
+    fn messages(&mut self, path: &Path) -> Result<&mut Messages> {
+        let messages = match self.messages.take() {
+            Some(messages) => messages,
+            None => Messages::create(path)?,
+        };
+        Ok(self.messages.insert(messages))
+    }
+
+    pub(super) fn write_parse_error_message(
+        &mut self,
+        path: &Path,
+        error: &ParseErrorRenderData,
+    ) -> Result<()> {
+        self.messages(path)?.write(Envelope::ParseError(ParseError {
+            source: SourceReference {
+                uri: Some(error.path.clone()),
+                location: error.line.map(|line| Location {
+                    line,
+                    column: error.column,
+                }),
+            },
+            message: error.message.clone(),
+        }))
+    }
+
+    pub(super) fn write_messages<W>(
+        &mut self,
+        path: &Path,
+        event: &event::Cucumber<W>,
+        at: SystemTime,
+    ) -> Result<()> {
+        if let event::Cucumber::Feature(feature, event::Feature::Started) = event {
+            self.add_feature(feature.clone().into());
+        }
+        let original = match event {
+            event::Cucumber::Feature(feature, event::Feature::Started) => self
+                .orig_features
+                .iter()
+                .find(|f| f.id() == feature.id())
+                .cloned(),
+            _ => None,
+        };
+        let messages = self.messages(path)?;
+        match event {
+            event::Cucumber::Started => {
+                messages.write_run(Envelope::TestRunStarted(TestRunStarted {
+                    timestamp: Timestamp::from(at),
+                }))?;
+            }
+            event::Cucumber::Feature(feature, event::Feature::Started) => {
+                let source = Source::read(feature);
+                if let Some(uri) = uri(feature) {
+                    messages.write(Envelope::Source(SourceNode {
+                        uri,
+                        data: source.text.clone(),
+                        media_type: "text/x.cucumber.gherkin+plain",
+                    }))?;
+                }
+                let original = original.as_deref().unwrap_or(feature);
+                let document = GherkinDocument::new(feature, original, &source);
+                messages.write(Envelope::GherkinDocument(document))?;
+                for (rule, scenario) in scenarios_with_rule(feature) {
+                    let pickle = Pickle::new(feature, rule, scenario, &source);
+                    messages.write(Envelope::Pickle(pickle))?;
+                }
+            }
+            event::Cucumber::Feature(feature, event::Feature::Scenario(scenario, event))
+            | event::Cucumber::Feature(
+                feature,
+                event::Feature::Rule(_, event::Rule::Scenario(scenario, event)),
+            ) => {
+                messages.scenario_event(feature, scenario, event, at)?;
+            }
+            event::Cucumber::Finished => {
+                let success = messages.success;
+                messages.write_run(Envelope::TestRunFinished(TestRunFinished {
+                    success,
+                    timestamp: Timestamp::from(at),
+                }))?;
+                messages.finish()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Messages {
+    fn scenario_event<W>(
+        &mut self,
+        feature: &Feature,
+        scenario: &Scenario,
+        event: &event::RetryableScenario<W>,
+        at: SystemTime,
+    ) -> Result<()> {
+        let pickle_id = pickle_id(feature, scenario);
+        let test_case_id = node_id(("test-case", &pickle_id));
+        let attempt = event.retries.map(|r| r.current).unwrap_or_default();
+        let test_case_started_id = node_id((&test_case_id, attempt));
+
+        if let event::Scenario::Finished = &event.event {
+            let progress = self
+                .test_cases
+                .remove(&test_case_started_id)
+                .unwrap_or_default();
+            return self.finish_test_case(feature, scenario, event, progress, at);
+        }
+        let progress = self
+            .test_cases
+            .entry(test_case_started_id.clone())
+            .or_default();
+        let (test_step_id, result) = match &event.event {
+            event::Scenario::Started => {
+                return progress.buffer(Envelope::TestCaseStarted(TestCaseStarted {
+                    attempt,
+                    id: test_case_started_id,
+                    test_case_id,
+                    timestamp: Timestamp::from(at),
+                }));
+            }
+            event::Scenario::Hook(ty, event) => {
+                let test_step_id = hook_test_step_id(&pickle_id, *ty);
+                let result = match event {
+                    event::Hook::Started => None,
+                    event::Hook::Passed => Some(TestStepResult::new("PASSED", None)),
+                    event::Hook::Failed(_, info) => {
+                        progress.failed = true;
+                        Some(TestStepResult::new("FAILED", Some(panic_payload(info))))
+                    }
+                };
+                if result.is_none() {
+                    progress.hooks.push(*ty);
+                }
+                (test_step_id, result)
+            }
+            event::Scenario::Step(step, event) | event::Scenario::Background(step, event) => {
+                let result = match event {
+                    event::Step::Started => None,
+                    event::Step::Passed(..) => Some(TestStepResult::new("PASSED", None)),
+                    event::Step::Skipped => Some(TestStepResult::new("UNDEFINED", None)),
+                    event::Step::Failed(_, _, _, error) => {
+                        progress.failed = true;
+                        let status = match error {
+                            StepError::NotFound => "UNDEFINED",
+                            StepError::AmbiguousMatch(_) => "AMBIGUOUS",
+                            StepError::Panic(_) => "FAILED",
+                        };
+                        let message = match error {
+                            StepError::Panic(info) => panic_payload(info),
+                            error => error.to_string(),
+                        };
+                        Some(TestStepResult::new(status, Some(message)))
+                    }
+                };
+                (test_step_id(&pickle_id, step), result)
+            }
+            _ => return Ok(()),
+        };
+        let Some(result) = result else {
+            progress.started.insert(test_step_id.clone(), at);
+            return progress.buffer(Envelope::TestStepStarted(TestStepStarted {
+                test_case_started_id,
+                test_step_id,
+                timestamp: Timestamp::from(at),
+            }));
+        };
+        let duration = progress
+            .started
+            .get(&test_step_id)
+            .and_then(|started| at.duration_since(*started).ok())
+            .unwrap_or_default();
+        progress.finished.insert(test_step_id.clone());
+        progress.buffer(Envelope::TestStepFinished(TestStepFinished {
+            test_case_started_id,
+            test_step_id,
+            test_step_result: TestStepResult {
+                duration: duration.into(),
+                ..result
+            },
+            timestamp: Timestamp::from(at),
+        }))
+    }
+
+    /// Writes the hooks that ran for it and, to the test run, the test case
+    /// the first time one of its attempts finished, followed by everything
+    /// of the attempt
+    fn finish_test_case<W>(
+        &mut self,
+        feature: &Feature,
+        scenario: &Scenario,
+        event: &event::RetryableScenario<W>,
+        progress: TestCaseProgress,
+        at: SystemTime,
+    ) -> Result<()> {
+        let pickle_id = pickle_id(feature, scenario);
+        let test_case = TestCase::new(feature, scenario, &progress.hooks);
+        let attempt = event.retries.map(|r| r.current).unwrap_or_default();
+        let test_case_started_id = node_id((&test_case.id, attempt));
+        for ty in &progress.hooks {
+            let hook = Hook::new(*ty);
+            if self.written.insert(hook.id.clone()) {
+                self.write(Envelope::Hook(hook))?;
+            }
+        }
+        if self.written.insert(test_case.id.clone()) {
+            self.write_run(Envelope::TestCase(test_case))?;
+        }
+        self.run.extend(progress.envelopes);
+        // steps after a failed one are never run, cucumber reports them as skipped
+        for step in pickle_steps(feature, scenario) {
+            let test_step_id = test_step_id(&pickle_id, step);
+            if !progress.finished.contains(&test_step_id) {
+                self.write_run(Envelope::TestStepStarted(TestStepStarted {
+                    test_case_started_id: test_case_started_id.clone(),
+                    test_step_id: test_step_id.clone(),
+                    timestamp: Timestamp::from(at),
+                }))?;
+                self.write_run(Envelope::TestStepFinished(TestStepFinished {
+                    test_case_started_id: test_case_started_id.clone(),
+                    test_step_id,
+                    test_step_result: TestStepResult::new("SKIPPED", None),
+                    timestamp: Timestamp::from(at),
+                }))?;
+            }
+        }
+        let will_be_retried = progress.failed && event.retries.is_some_and(|r| r.left > 0);
+        if progress.failed && !will_be_retried {
+            self.success = false;
+        }
+        self.write_run(Envelope::TestCaseFinished(TestCaseFinished {
+            test_case_started_id,
+            timestamp: Timestamp::from(at),
+            will_be_retried,
+        }))
+    }
+}
+
+/// Ids only need to be unique within a run, so they are derived from the
+/// gherkin source. Spans are only unique within a feature file.
+fn node_id(value: impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn ast_id(feature: &Feature, span: Span) -> String {
+    node_id((feature.id(), span))
+}
+
+fn row_id(feature: &Feature, examples: &Examples, row: usize) -> String {
+    node_id((feature.id(), examples.span, row))
+}
+
+/// Scenarios expanded from an outline share their span, the position is
+/// the one of their example row
+fn pickle_id(feature: &Feature, scenario: &Scenario) -> String {
+    node_id(("pickle", feature.id(), scenario.span, scenario.position))
+}
+
+fn pickle_step_id(pickle_id: &str, step: &Step) -> String {
+    node_id(("pickle-step", pickle_id, step.span))
+}
+
+fn test_step_id(pickle_id: &str, step: &Step) -> String {
+    node_id(("test-step", pickle_id, step.span))
+}
+
+fn hook_test_step_id(pickle_id: &str, ty: HookType) -> String {
+    node_id(("test-step", pickle_id, hook_type(ty)))
+}
+
+/// Index of the example row an expanded scenario was created from
+fn example_row(scenario: &Scenario) -> Option<(&Examples, usize)> {
+    scenario.examples.first().map(|ex| {
+        (
+            ex,
+            scenario.position.line.saturating_sub(ex.position.line + 2),
+        )
+    })
+}
+
+fn pickle_steps<'a>(feature: &'a Feature, scenario: &'a Scenario) -> Vec<&'a Step> {
+    backgrounds(feature, scenario)
+        .into_iter()
+        .flat_map(|b| b.steps.iter())
+        .chain(scenario.steps.iter())
+        .collect()
+}
+
+fn uri(feature: &Feature) -> Option<String> {
+    feature.path.as_deref().map(display_path)
+}
+
+fn hook_type(ty: HookType) -> &'static str {
+    match ty {
+        HookType::Before => "BEFORE_TEST_CASE",
+        HookType::After => "AFTER_TEST_CASE",
+    }
+}
+
+fn keyword_type(ty: StepType) -> &'static str {
+    match ty {
+        StepType::Given => "Context",
+        StepType::When => "Action",
+        StepType::Then => "Outcome",
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Envelope {
+    Meta(Meta),
+    Source(SourceNode),
+    ParseError(ParseError),
+    GherkinDocument(GherkinDocument),
+    Pickle(Pickle),
+    Hook(Hook),
+    TestCase(TestCase),
+    TestRunStarted(TestRunStarted),
+    TestCaseStarted(TestCaseStarted),
+    TestStepStarted(TestStepStarted),
+    TestStepFinished(TestStepFinished),
+    TestCaseFinished(TestCaseFinished),
+    TestRunFinished(TestRunFinished),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    protocol_version: &'static str,
+    implementation: Product,
+    runtime: Product,
+    os: Product,
+    cpu: Product,
+}
+
+#[derive(Serialize)]
+struct Product {
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'static str>,
+}
+
+impl Meta {
+    fn new() -> Self {
+        Self {
+            protocol_version: "27.0.0",
+            implementation: Product {
+                name: env!("CARGO_PKG_NAME"),
+                version: Some(env!("CARGO_PKG_VERSION")),
+            },
+            runtime: Product {
+                name: "rust",
+                version: None,
+            },
+            os: Product {
+                name: std::env::consts::OS,
+                version: None,
+            },
+            cpu: Product {
+                name: std::env::consts::ARCH,
+                version: None,
+            },
+        }
+    }
+}
+
+/// Timestamps and durations are both seconds and nanos
+#[derive(Serialize)]
+struct Timestamp {
+    seconds: u64,
+    nanos: u32,
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(at: SystemTime) -> Self {
+        at.duration_since(UNIX_EPOCH).unwrap_or_default().into()
+    }
+}
+
+impl From<Duration> for Timestamp {
+    fn from(duration: Duration) -> Self {
+        Self {
+            seconds: duration.as_secs(),
+            nanos: duration.subsec_nanos(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Location {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+impl From<LineCol> for Location {
+    fn from(position: LineCol) -> Self {
+        Self {
+            line: position.line,
+            column: Some(position.col),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceReference {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+}
+
+#[derive(Serialize)]
+struct ParseError {
+    source: SourceReference,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceNode {
+    uri: String,
+    data: String,
+    media_type: &'static str,
+}
+
+/// What gherkin does not keep of a feature file: its text, its language and
+/// the line of every table row
+#[derive(Default)]
+struct Source {
+    text: String,
+    lines: Vec<String>,
+}
+
+impl Source {
+    fn read(feature: &Feature) -> Self {
+        let text = feature
+            .path
+            .as_deref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let lines = text.lines().map(str::to_string).collect();
+        Self { text, lines }
+    }
+
+    /// The `# language:` directive, only blank lines may come before it
+    fn language(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+            .and_then(|line| line.strip_prefix('#'))
+            .and_then(|line| line.trim_start().strip_prefix("language"))
+            .and_then(|line| line.trim_start().strip_prefix(':'))
+            .map(|language| language.trim().to_string())
+            .unwrap_or_else(|| "en".to_string())
+    }
+
+    /// Rows of a table with their line, blank lines and comments can be
+    /// between them. Without the file the rows are taken to follow each other.
+    fn table_rows<'a>(&self, table: &'a Table) -> Vec<(LineCol, &'a Vec<String>)> {
+        let mut lines = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(table.position.line.saturating_sub(1))
+            .filter(|(_, line)| line.trim_start().starts_with('|'))
+            .map(|(id, line)| LineCol {
+                line: id + 1,
+                col: line.len() - line.trim_start().len() + 1,
+            });
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(id, cells)| {
+                let position = lines.next().unwrap_or(LineCol {
+                    line: table.position.line + id,
+                    col: table.position.col,
+                });
+                (position, cells)
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct GherkinDocument {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    feature: FeatureNode,
+    comments: Vec<()>,
+}
+
+impl GherkinDocument {
+    /// `original` is the feature as written, before its outlines were expanded
+    fn new(feature: &Feature, original: &Feature, source: &Source) -> Self {
+        let children = original
+            .background
+            .iter()
+            .map(|b| FeatureChild::background(feature, b, source))
+            .chain(
+                original
+                    .scenarios
+                    .iter()
+                    .map(|s| FeatureChild::scenario(feature, s, source)),
+            )
+            .chain(
+                original
+                    .rules
+                    .iter()
+                    .map(|r| FeatureChild::rule(feature, r, source)),
+            )
+            .collect();
+        Self {
+            uri: uri(feature),
+            feature: FeatureNode {
+                location: original.position.into(),
+                tags: tags(feature, original.span, original.position, &original.tags),
+                language: source.language(),
+                keyword: original.keyword.clone(),
+                name: original.name.clone(),
+                description: original.description.clone().unwrap_or_default(),
+                children,
+            },
+            comments: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FeatureNode {
+    location: Location,
+    tags: Vec<Tag>,
+    language: String,
+    keyword: String,
+    name: String,
+    description: String,
+    children: Vec<FeatureChild>,
+}
+
+#[derive(Serialize, Default)]
+struct FeatureChild {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule: Option<RuleNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    background: Option<BackgroundNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scenario: Option<ScenarioNode>,
+}
+
+impl FeatureChild {
+    fn background(feature: &Feature, background: &Background, source: &Source) -> Self {
+        Self {
+            background: Some(BackgroundNode {
+                location: background.position.into(),
+                keyword: background.keyword.clone(),
+                name: background.name.clone(),
+                description: background.description.clone().unwrap_or_default(),
+                steps: background
+                    .steps
+                    .iter()
+                    .map(|s| StepNode::new(feature, s, source))
+                    .collect(),
+                id: ast_id(feature, background.span),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn scenario(feature: &Feature, scenario: &Scenario, source: &Source) -> Self {
+        Self {
+            scenario: Some(ScenarioNode::new(feature, scenario, source)),
+            ..Default::default()
+        }
+    }
+
+    fn rule(feature: &Feature, rule: &Rule, source: &Source) -> Self {
+        let children = rule
+            .background
+            .iter()
+            .map(|b| FeatureChild::background(feature, b, source))
+            .chain(
+                rule.scenarios
+                    .iter()
+                    .map(|s| FeatureChild::scenario(feature, s, source)),
+            )
+            .collect();
+        Self {
+            rule: Some(RuleNode {
+                location: rule.position.into(),
+                tags: tags(feature, rule.span, rule.position, &rule.tags),
+                keyword: rule.keyword.clone(),
+                name: rule.name.clone(),
+                description: rule.description.clone().unwrap_or_default(),
+                children,
+                id: ast_id(feature, rule.span),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RuleNode {
+    location: Location,
+    tags: Vec<Tag>,
+    keyword: String,
+    name: String,
+    description: String,
+    children: Vec<FeatureChild>,
+    id: String,
+}
+
+#[derive(Serialize)]
+struct BackgroundNode {
+    location: Location,
+    keyword: String,
+    name: String,
+    description: String,
+    steps: Vec<StepNode>,
+    id: String,
+}
+
+#[derive(Serialize)]
+struct ScenarioNode {
+    location: Location,
+    tags: Vec<Tag>,
+    keyword: String,
+    name: String,
+    description: String,
+    steps: Vec<StepNode>,
+    examples: Vec<ExamplesNode>,
+    id: String,
+}
+
+impl ScenarioNode {
+    fn new(feature: &Feature, scenario: &Scenario, source: &Source) -> Self {
+        Self {
+            location: scenario.position.into(),
+            tags: tags(feature, scenario.span, scenario.position, &scenario.tags),
+            keyword: scenario.keyword.clone(),
+            name: scenario.name.clone(),
+            description: scenario.description.clone().unwrap_or_default(),
+            steps: scenario
+                .steps
+                .iter()
+                .map(|s| StepNode::new(feature, s, source))
+                .collect(),
+            examples: scenario
+                .examples
+                .iter()
+                .map(|ex| ExamplesNode::new(feature, ex, source))
+                .collect(),
+            id: ast_id(feature, scenario.span),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StepNode {
+    location: Location,
+    keyword: String,
+    keyword_type: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc_string: Option<DocStringNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_table: Option<DataTableNode>,
+    id: String,
+}
+
+impl StepNode {
+    fn new(feature: &Feature, step: &Step, source: &Source) -> Self {
+        Self {
+            location: step.position.into(),
+            keyword: step.keyword.clone(),
+            keyword_type: keyword_type(step.ty),
+            text: step.value.clone(),
+            doc_string: step.docstring.as_deref().map(|docstring| {
+                let docstring = DocStringRenderData::from(docstring);
+                DocStringNode {
+                    location: step.position.into(),
+                    media_type: docstring.content_type,
+                    content: docstring.content,
+                    delimiter: "\"\"\"",
+                }
+            }),
+            data_table: step.table.as_ref().map(|table| DataTableNode {
+                location: table.position.into(),
+                rows: source
+                    .table_rows(table)
+                    .into_iter()
+                    .map(|(line, cells)| {
+                        TableRowNode::new(line, cells, node_id((ast_id(feature, table.span), line)))
+                    })
+                    .collect(),
+            }),
+            id: ast_id(feature, step.span),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DocStringNode {
+    location: Location,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+    content: String,
+    delimiter: &'static str,
+}
+
+#[derive(Serialize)]
+struct DataTableNode {
+    location: Location,
+    rows: Vec<TableRowNode>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExamplesNode {
+    location: Location,
+    tags: Vec<Tag>,
+    keyword: String,
+    name: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    table_header: Option<TableRowNode>,
+    table_body: Vec<TableRowNode>,
+    id: String,
+}
+
+impl ExamplesNode {
+    fn new(feature: &Feature, examples: &Examples, source: &Source) -> Self {
+        let mut rows = examples
+            .table
+            .iter()
+            .flat_map(|table| source.table_rows(table))
+            .enumerate()
+            .map(|(id, (line, cells))| {
+                let row_id = match id {
+                    0 => node_id((ast_id(feature, examples.span), "header")),
+                    _ => row_id(feature, examples, id - 1),
+                };
+                TableRowNode::new(line, cells, row_id)
+            });
+        Self {
+            location: examples.position.into(),
+            tags: tags(feature, examples.span, examples.position, &examples.tags),
+            keyword: examples.keyword.clone(),
+            name: examples.name.clone().unwrap_or_default(),
+            description: examples.description.clone().unwrap_or_default(),
+            table_header: rows.next(),
+            table_body: rows.collect(),
+            id: ast_id(feature, examples.span),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TableRowNode {
+    location: Location,
+    cells: Vec<TableCellNode>,
+    id: String,
+}
+
+impl TableRowNode {
+    fn new(position: LineCol, cells: &[String], id: String) -> Self {
+        Self {
+            location: position.into(),
+            cells: cells
+                .iter()
+                .map(|value| TableCellNode {
+                    location: position.into(),
+                    value: value.clone(),
+                })
+                .collect(),
+            id,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TableCellNode {
+    location: Location,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct Tag {
+    location: Location,
+    name: String,
+    id: String,
+}
+
+fn tags(feature: &Feature, span: Span, position: LineCol, tags: &[String]) -> Vec<Tag> {
+    tags.iter()
+        .map(|tag| Tag {
+            location: position.into(),
+            name: format!("@{tag}"),
+            id: tag_id(feature, span, tag),
+        })
+        .collect()
+}
+
+fn tag_id(feature: &Feature, span: Span, tag: &str) -> String {
+    node_id((feature.id(), span, tag))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Pickle {
+    id: String,
+    uri: String,
+    name: String,
+    language: String,
+    steps: Vec<PickleStep>,
+    tags: Vec<PickleTag>,
+    ast_node_ids: Vec<String>,
+}
+
+impl Pickle {
+    fn new(feature: &Feature, rule: Option<&Rule>, scenario: &Scenario, source: &Source) -> Self {
+        let id = pickle_id(feature, scenario);
+        let row_id = example_row(scenario).map(|(ex, row)| row_id(feature, ex, row));
+        let tags = feature
+            .tags
+            .iter()
+            .map(|t| (feature.span, t))
+            .chain(rule.iter().flat_map(|r| r.tags.iter().map(|t| (r.span, t))))
+            .chain(scenario.tags.iter().map(|t| (scenario.span, t)))
+            .map(|(span, tag)| PickleTag {
+                name: format!("@{tag}"),
+                ast_node_id: tag_id(feature, span, tag),
+            })
+            .collect();
+        Self {
+            steps: pickle_steps(feature, scenario)
+                .into_iter()
+                .map(|step| PickleStep {
+                    ast_node_ids: std::iter::once(ast_id(feature, step.span))
+                        .chain(row_id.clone())
+                        .collect(),
+                    id: pickle_step_id(&id, step),
+                    r#type: keyword_type(step.ty),
+                    text: step.value.clone(),
+                    argument: PickleStepArgument::new(step),
+                })
+                .collect(),
+            ast_node_ids: std::iter::once(ast_id(feature, scenario.span))
+                .chain(row_id)
+                .collect(),
+            uri: uri(feature).unwrap_or_default(),
+            name: scenario.name.clone(),
+            language: source.language(),
+            tags,
+            id,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PickleStep {
+    ast_node_ids: Vec<String>,
+    id: String,
+    r#type: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    argument: Option<PickleStepArgument>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PickleStepArgument {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc_string: Option<PickleDocString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_table: Option<PickleTable>,
+}
+
+impl PickleStepArgument {
+    fn new(step: &Step) -> Option<Self> {
+        if step.docstring.is_none() && step.table.is_none() {
+            return None;
+        }
+        Some(Self {
+            doc_string: step.docstring.as_deref().map(|docstring| {
+                let docstring = DocStringRenderData::from(docstring);
+                PickleDocString {
+                    media_type: docstring.content_type,
+                    content: docstring.content,
+                }
+            }),
+            data_table: step.table.as_ref().map(|table| PickleTable {
+                rows: table
+                    .rows
+                    .iter()
+                    .map(|row| PickleTableRow {
+                        cells: row
+                            .iter()
+                            .map(|value| PickleTableCell {
+                                value: value.clone(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            }),
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PickleDocString {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct PickleTable {
+    rows: Vec<PickleTableRow>,
+}
+
+#[derive(Serialize)]
+struct PickleTableRow {
+    cells: Vec<PickleTableCell>,
+}
+
+#[derive(Serialize)]
+struct PickleTableCell {
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PickleTag {
+    name: String,
+    ast_node_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestCase {
+    id: String,
+    pickle_id: String,
+    test_steps: Vec<TestStep>,
+}
+
+impl TestCase {
+    /// `hooks` are the hooks that ran for the scenario, cucumber runs them
+    /// around all of its steps
+    fn new(feature: &Feature, scenario: &Scenario, hooks: &[HookType]) -> Self {
+        let pickle_id = pickle_id(feature, scenario);
+        let hook_step = |ty| {
+            let ran = hooks.iter().any(|ran| hook_type(*ran) == hook_type(ty));
+            ran.then(|| TestStep {
+                hook_id: Some(hook_id(ty)),
+                id: hook_test_step_id(&pickle_id, ty),
+                ..Default::default()
+            })
+        };
+        let steps = pickle_steps(feature, scenario)
+            .into_iter()
+            .map(|step| TestStep {
+                id: test_step_id(&pickle_id, step),
+                pickle_step_id: Some(pickle_step_id(&pickle_id, step)),
+                step_definition_ids: Some(Vec::new()),
+                step_match_arguments_lists: Some(Vec::new()),
+                ..Default::default()
+            });
+        Self {
+            id: node_id(("test-case", &pickle_id)),
+            test_steps: hook_step(HookType::Before)
+                .into_iter()
+                .chain(steps)
+                .chain(hook_step(HookType::After))
+                .collect(),
+            pickle_id,
+        }
+    }
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct TestStep {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hook_id: Option<String>,
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pickle_step_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_definition_ids: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_match_arguments_lists: Option<Vec<()>>,
+}
+
+/// The hooks are closures without a name or a location
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Hook {
+    id: String,
+    r#type: &'static str,
+    source_reference: SourceReference,
+}
+
+impl Hook {
+    fn new(ty: HookType) -> Self {
+        Self {
+            id: hook_id(ty),
+            r#type: hook_type(ty),
+            source_reference: SourceReference {
+                uri: None,
+                location: None,
+            },
+        }
+    }
+}
+
+fn hook_id(ty: HookType) -> String {
+    node_id(("hook", hook_type(ty)))
+}
+
+#[derive(Serialize)]
+struct TestRunStarted {
+    timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+struct TestRunFinished {
+    success: bool,
+    timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestCaseStarted {
+    attempt: usize,
+    id: String,
+    test_case_id: String,
+    timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestCaseFinished {
+    test_case_started_id: String,
+    timestamp: Timestamp,
+    will_be_retried: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestStepStarted {
+    test_case_started_id: String,
+    test_step_id: String,
+    timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestStepFinished {
+    test_case_started_id: String,
+    test_step_id: String,
+    test_step_result: TestStepResult,
+    timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+struct TestStepResult {
+    duration: Timestamp,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl TestStepResult {
+    fn new(status: &'static str, message: Option<String>) -> Self {
+        Self {
+            duration: Duration::ZERO.into(),
+            status,
+            message,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use gherkin::{Examples, Feature, Scenario, Table};
+use std::collections::HashSet;
+
+/// The feature as written. Cucumber replaces an outline by a scenario per
+/// example row. The expanded scenarios share the span of their outline and
+/// are merged back here, guessing the `<placeholders>` from the values of the
+/// example rows.
+pub(super) fn merged_feature(feature: &Feature) -> Feature {
+    let mut original = feature.clone();
+    original.scenarios = original_scenarios(&feature.scenarios);
+    for rule in &mut original.rules {
+        rule.scenarios = original_scenarios(&rule.scenarios);
+    }
+    original
+}
+
+fn original_scenarios(scenarios: &[Scenario]) -> Vec<Scenario> {
+    let mut original = Vec::<Scenario>::new();
+    for scenario in scenarios {
+        if scenario.examples.is_empty() {
+            original.push(scenario.clone());
+        } else if !original.iter().any(|s| s.span == scenario.span) {
+            let expanded = scenarios
+                .iter()
+                .filter(|s| s.span == scenario.span)
+                .collect::<Vec<_>>();
+            original.push(outline(&expanded));
+        }
+    }
+    original
+}
+
+/// An expanded scenario has one example, with the header and its own row
+fn example_row(scenario: &Scenario) -> Option<(&Examples, &Vec<String>, &Vec<String>)> {
+    let example = scenario.examples.first()?;
+    let table = example.table.as_ref()?;
+    Some((example, table.rows.first()?, table.rows.get(1)?))
+}
+
+fn outline(expanded: &[&Scenario]) -> Scenario {
+    let mut outline = expanded[0].clone();
+
+    // the line of an expanded scenario numbers the rows of its examples
+    let mut rows = expanded
+        .iter()
+        .filter_map(|scenario| example_row(scenario).map(|row| (scenario.position.line, row)))
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|(line, _)| *line);
+
+    let mut examples = Vec::<Examples>::new();
+    for (_, (example, header, values)) in &rows {
+        match examples.iter_mut().find(|e| e.span == example.span) {
+            Some(Examples {
+                table: Some(table), ..
+            }) => table.rows.push(values.to_vec()),
+            _ => {
+                let mut example = (*example).clone();
+                example.table = example.table.map(|table| Table {
+                    rows: vec![header.to_vec(), values.to_vec()],
+                    ..table
+                });
+                examples.push(example);
+            }
+        }
+    }
+    examples.sort_by_key(|e| e.span.start);
+
+    // the tags of the example were added to the tags of the outline
+    let example_tags = expanded[0].examples.first().map_or(0, |e| e.tags.len());
+    outline
+        .tags
+        .truncate(outline.tags.len().saturating_sub(example_tags));
+
+    let mut templates = Templates {
+        rows: expanded
+            .iter()
+            .map(|scenario| {
+                example_row(scenario)
+                    .map(|(_, header, values)| header.iter().zip(values.iter()).collect())
+                    .unwrap_or_default()
+            })
+            .collect(),
+        used: HashSet::new(),
+    };
+    outline.name = templates.template(expanded.iter().map(|s| s.name.as_str()));
+    for (id, step) in outline.steps.iter_mut().enumerate() {
+        let steps = expanded
+            .iter()
+            .filter_map(|scenario| scenario.steps.get(id))
+            .collect::<Vec<_>>();
+        step.value = templates.template(steps.iter().map(|s| s.value.as_str()));
+        if step.docstring.is_some() {
+            step.docstring = Some(
+                templates.template(
+                    steps
+                        .iter()
+                        .map(|s| s.docstring.as_deref().unwrap_or_default()),
+                ),
+            );
+        }
+        if let Some(table) = &mut step.table {
+            for (row_id, row) in table.rows.iter_mut().enumerate() {
+                for (cell_id, cell) in row.iter_mut().enumerate() {
+                    *cell = templates.template(steps.iter().map(|s| {
+                        s.table
+                            .as_ref()
+                            .and_then(|t| t.rows.get(row_id))
+                            .and_then(|r| r.get(cell_id))
+                            .map(String::as_str)
+                            .unwrap_or_default()
+                    }));
+                }
+            }
+        }
+    }
+    // the position of an expanded scenario is that of its example row, the
+    // line of the outline itself is gone. Its steps are below it and its
+    // description, taken to be followed by a blank line, so this is only close.
+    if let Some(step) = outline.steps.first() {
+        let description = outline
+            .description
+            .as_ref()
+            .map_or(0, |description| description.lines().count() + 1);
+        outline.position.line = step.position.line.saturating_sub(1 + description);
+    }
+    outline.examples = examples;
+    outline
+}
+
+/// Finds the text of an outline before cucumber replaced its `<placeholders>`,
+/// from the texts of the expanded scenarios and the example rows they came from.
+/// Only a column with different values per row can be recognized. When columns
+/// have the same values a column not used before in the outline is taken, as
+/// outlines mostly use every column once.
+struct Templates<'a> {
+    /// Header and value of every column, per expanded scenario
+    rows: Vec<Vec<(&'a String, &'a String)>>,
+    used: HashSet<&'a str>,
+}
+
+impl<'a> Templates<'a> {
+    fn template<'t>(&mut self, texts: impl Iterator<Item = &'t str>) -> String {
+        let texts = texts.collect::<Vec<_>>();
+        if texts.len() != self.rows.len() || texts.is_empty() {
+            return texts.first().map(|t| t.to_string()).unwrap_or_default();
+        }
+        let mut positions = vec![0; texts.len()];
+        let mut template = String::new();
+        let mut used = Vec::new();
+
+        while positions.iter().zip(&texts).any(|(p, t)| *p < t.len()) {
+            let rest = texts
+                .iter()
+                .zip(&positions)
+                .map(|(text, position)| &text[*position..])
+                .collect::<Vec<_>>();
+
+            let mut placeholders = self.rows[0]
+                .iter()
+                .map(|(header, _)| *header)
+                .filter(|header| {
+                    let values = self
+                        .rows
+                        .iter()
+                        .map(|row| row.iter().find(|(h, _)| h == header).map(|(_, v)| *v))
+                        .collect::<Option<Vec<_>>>()
+                        .unwrap_or_default();
+                    values.len() == rest.len()
+                        && values.iter().any(|v| v != &values[0])
+                        && values.iter().zip(&rest).all(|(value, rest)| {
+                            !value.is_empty() && rest.starts_with(value.as_str())
+                        })
+                })
+                .collect::<Vec<_>>();
+            placeholders.sort_by_key(|header| self.used.contains(header.as_str()));
+
+            if let Some(header) = placeholders.first() {
+                for (position, row) in positions.iter_mut().zip(&self.rows) {
+                    if let Some((_, value)) = row.iter().find(|(h, _)| h == header) {
+                        *position += value.len();
+                    }
+                }
+                template.push_str(&format!("<{header}>"));
+                used.push(header.as_str());
+            } else if let Some(c) = rest[0].chars().next()
+                && rest.iter().all(|r| r.starts_with(c))
+            {
+                for position in positions.iter_mut() {
+                    *position += c.len_utf8();
+                }
+                template.push(c);
+            } else {
+                // the rows can not be explained, show the first one as is
+                return texts[0].to_string();
+            }
+        }
+        self.used.extend(used);
+        template
+    }
+}
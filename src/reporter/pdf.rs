@@ -0,0 +1,685 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use crate::render_types::*;
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+/// Width of the column at the right of a step with its outcome
+const OUTCOME_WIDTH: f32 = 90.0;
+
+/// The report as a single paginated pdf, a cover page with the statistics of
+/// the index followed by a section per feature
+pub(super) fn pdf_render(title: &str, report: &ReportData) -> Vec<u8> {
+    let mut document = PdfDocument::new();
+    document.cover(title, report);
+    for feature in &report.features {
+        document.feature(feature);
+    }
+    document.finish(title)
+}
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn name(self) -> Name<'static> {
+        match self {
+            Font::Regular => Name(b"F1"),
+            Font::Bold => Name(b"F2"),
+            Font::Mono => Name(b"F3"),
+        }
+    }
+
+    /// Average width of a character relative to the font size, the standard
+    /// fonts are not measured so lines are wrapped on an estimate
+    fn char_width(self) -> f32 {
+        match self {
+            Font::Regular => 0.5,
+            Font::Bold => 0.55,
+            Font::Mono => 0.6,
+        }
+    }
+}
+
+type Color = (f32, f32, f32);
+
+const BLACK: Color = (0.2, 0.2, 0.2);
+const GREY: Color = (0.45, 0.45, 0.45);
+const BLUE: Color = (0.0, 0.25, 0.5);
+
+fn state_color(state: &StepState) -> Color {
+    match state {
+        StepState::Passed => (0.0, 0.45, 0.0),
+        StepState::Failed | StepState::Ambiguous => (0.7, 0.13, 0.13),
+        StepState::Undefined => (0.85, 0.45, 0.0),
+        StepState::Skipped | StepState::NotRun => GREY,
+    }
+}
+
+fn state_name(state: &StepState) -> &'static str {
+    match state {
+        StepState::Passed => "Passed",
+        StepState::Failed => "Failed",
+        StepState::Skipped => "Skipped",
+        StepState::Undefined => "Undefined",
+        StepState::Ambiguous => "Ambiguous",
+        StepState::NotRun => "Not run",
+    }
+}
+
+/// The standard fonts use WinAnsiEncoding, latin-1 and some typographic
+/// characters. Any other character, e.g. cyrillic or CJK, is shown as `?`,
+/// only an embedded unicode font could show those.
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x09 => b' ',
+            0x20..=0x7e | 0xa0..=0xff => c as u8,
+            _ => WIN_ANSI
+                .iter()
+                .find(|(win_ansi, _)| *win_ansi == c)
+                .map_or(b'?', |(_, code)| *code),
+        })
+        .collect()
+}
+
+/// The characters WinAnsiEncoding has at 0x80..=0x9f instead of latin-1
+const WIN_ANSI: [(char, u8); 27] = [
+    ('€', 0x80),
+    ('‚', 0x82),
+    ('ƒ', 0x83),
+    ('„', 0x84),
+    ('…', 0x85),
+    ('†', 0x86),
+    ('‡', 0x87),
+    ('ˆ', 0x88),
+    ('‰', 0x89),
+    ('Š', 0x8a),
+    ('‹', 0x8b),
+    ('Œ', 0x8c),
+    ('Ž', 0x8e),
+    ('‘', 0x91),
+    ('’', 0x92),
+    ('“', 0x93),
+    ('”', 0x94),
+    ('•', 0x95),
+    ('–', 0x96),
+    ('—', 0x97),
+    ('˜', 0x98),
+    ('™', 0x99),
+    ('š', 0x9a),
+    ('›', 0x9b),
+    ('œ', 0x9c),
+    ('ž', 0x9e),
+    ('Ÿ', 0x9f),
+];
+
+struct PdfDocument {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl PdfDocument {
+    fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            y: 0.0,
+        }
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Starts a new page when there is no room left for `height`
+    fn space(&mut self, height: f32) {
+        if self.pages.is_empty() || self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn text(&mut self, x: f32, font: Font, size: f32, color: Color, text: &str) {
+        let y = self.y;
+        if let Some(content) = self.pages.last_mut() {
+            content
+                .set_fill_rgb(color.0, color.1, color.2)
+                .begin_text()
+                .set_font(font.name(), size)
+                .next_line(x, y)
+                .show(Str(&encode(text)))
+                .end_text();
+        }
+    }
+
+    /// Writes `text` wrapped to the width of the page, every line of it
+    /// starts at `indent`
+    fn paragraph(&mut self, indent: f32, font: Font, size: f32, color: Color, text: &str) {
+        self.wrapped(indent, 0.0, font, size, color, text);
+    }
+
+    /// Writes `text` like [`Self::paragraph`], keeping `reserved` at the right
+    /// of the page free
+    fn wrapped(
+        &mut self,
+        indent: f32,
+        reserved: f32,
+        font: Font,
+        size: f32,
+        color: Color,
+        text: &str,
+    ) {
+        let width = PAGE_WIDTH - 2.0 * MARGIN - indent - reserved;
+        let max_chars = ((width / (size * font.char_width())) as usize).max(10);
+        for line in text.lines() {
+            for part in wrap(line, max_chars) {
+                self.space(size * 1.4);
+                self.y -= size * 1.4;
+                self.text(MARGIN + indent, font, size, color, &part);
+            }
+        }
+    }
+
+    /// A row of a table, the cells are cut off at the next column
+    fn row(&mut self, columns: &[(f32, String, Color)], font: Font, size: f32) {
+        self.space(size * 1.5);
+        self.y -= size * 1.5;
+        for (id, (x, text, color)) in columns.iter().enumerate() {
+            let end = columns
+                .get(id + 1)
+                .map_or(PAGE_WIDTH - MARGIN, |(next, _, _)| *next);
+            let max_chars = ((end - x - 4.0) / (size * font.char_width())) as usize;
+            let text = truncate(text, max_chars);
+            self.text(MARGIN + x, font, size, *color, &text);
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn cover(&mut self, title: &str, report: &ReportData) {
+        self.new_page();
+        self.paragraph(0.0, Font::Bold, 20.0, BLUE, title);
+        self.gap(6.0);
+        self.row(
+            &[
+                (0.0, "Status:".to_string(), BLACK),
+                (
+                    60.0,
+                    state_name(&report.status).to_string(),
+                    state_color(&report.status),
+                ),
+            ],
+            Font::Bold,
+            12.0,
+        );
+        if let Some(duration) = &report.duration {
+            self.paragraph(
+                0.0,
+                Font::Regular,
+                10.0,
+                BLACK,
+                &format!("Total run time: {}", duration.display),
+            );
+        }
+
+        self.gap(10.0);
+        const COLUMNS: [f32; 8] = [0.0, 190.0, 240.0, 280.0, 320.0, 365.0, 410.0, 450.0];
+        let headers = [
+            "Feature",
+            "Scenarios",
+            "Steps",
+            "Errors",
+            "Undefined",
+            "Skipped",
+            "Not run",
+            "Duration",
+        ];
+        self.row(
+            &COLUMNS
+                .iter()
+                .zip(headers)
+                .map(|(x, header)| (*x, header.to_string(), BLACK))
+                .collect::<Vec<_>>(),
+            Font::Bold,
+            8.0,
+        );
+        for feature in &report.features {
+            let stats = &feature.stats;
+            let name = match &stats.error {
+                Some(_) => format!("{} (not reported)", stats.name),
+                None => stats.name.clone(),
+            };
+            let values = [
+                name,
+                stats.nr_scenarios.to_string(),
+                stats.nr_steps.to_string(),
+                stats.nr_errors.to_string(),
+                stats.nr_undefined.to_string(),
+                stats.nr_skipped.to_string(),
+                stats.nr_not_run.to_string(),
+                stats
+                    .duration
+                    .as_ref()
+                    .map(|d| d.display.clone())
+                    .unwrap_or_default(),
+            ];
+            let failed = stats.error.is_some() || stats.nr_errors > 0 || stats.nr_ambiguous > 0;
+            let color = if failed {
+                state_color(&StepState::Failed)
+            } else {
+                BLACK
+            };
+            self.row(
+                &COLUMNS
+                    .iter()
+                    .zip(values)
+                    .map(|(x, value)| (*x, value, color))
+                    .collect::<Vec<_>>(),
+                Font::Regular,
+                8.0,
+            );
+        }
+
+        if !report.parse_errors.is_empty() {
+            self.gap(10.0);
+            self.paragraph(
+                0.0,
+                Font::Bold,
+                12.0,
+                state_color(&StepState::Failed),
+                "Parse errors",
+            );
+            for error in &report.parse_errors {
+                let position = match (error.line, error.column) {
+                    (Some(line), Some(column)) => format!("{}:{line}:{column}", error.path),
+                    _ => error.path.clone(),
+                };
+                self.paragraph(0.0, Font::Bold, 8.0, BLACK, &position);
+                self.paragraph(10.0, Font::Mono, 7.0, BLACK, &error.message);
+            }
+        }
+
+        if !report.tags.is_empty() {
+            self.gap(10.0);
+            self.paragraph(0.0, Font::Bold, 12.0, BLACK, "Tags");
+            const COLUMNS: [f32; 6] = [0.0, 190.0, 250.0, 300.0, 350.0, 410.0];
+            let headers = [
+                "Tag",
+                "Scenarios",
+                "Passed",
+                "Failed",
+                "Undefined",
+                "Skipped",
+            ];
+            self.row(
+                &COLUMNS
+                    .iter()
+                    .zip(headers)
+                    .map(|(x, header)| (*x, header.to_string(), BLACK))
+                    .collect::<Vec<_>>(),
+                Font::Bold,
+                8.0,
+            );
+            for tag in &report.tags {
+                let values = [
+                    format!("@{}", tag.name),
+                    tag.nr_scenarios.to_string(),
+                    tag.nr_passed.to_string(),
+                    tag.nr_failed.to_string(),
+                    tag.nr_undefined.to_string(),
+                    tag.nr_skipped.to_string(),
+                ];
+                self.row(
+                    &COLUMNS
+                        .iter()
+                        .zip(values)
+                        .map(|(x, value)| (*x, value, BLACK))
+                        .collect::<Vec<_>>(),
+                    Font::Regular,
+                    8.0,
+                );
+            }
+        }
+    }
+
+    fn feature(&mut self, feature: &FeatureReportData) {
+        let stats = &feature.stats;
+        self.new_page();
+        self.paragraph(
+            0.0,
+            Font::Bold,
+            16.0,
+            BLUE,
+            &format!("Feature: {}", stats.name),
+        );
+        self.tags(0.0, &stats.tags);
+        if let Some(duration) = &stats.duration {
+            self.paragraph(0.0, Font::Regular, 8.0, GREY, &duration.display);
+        }
+        if !stats.description.is_empty() {
+            self.paragraph(0.0, Font::Regular, 9.0, BLACK, &stats.description);
+        }
+        if let Some(error) = &stats.error {
+            self.paragraph(
+                0.0,
+                Font::Bold,
+                10.0,
+                state_color(&StepState::Failed),
+                "Not reported",
+            );
+            self.paragraph(0.0, Font::Mono, 8.0, state_color(&StepState::Failed), error);
+        }
+        if let Some(background) = &feature.background {
+            self.background(0.0, background);
+        }
+        for scenario in &feature.scenarios {
+            self.scenario(0.0, scenario);
+        }
+        for rule in &feature.rules {
+            self.gap(8.0);
+            self.space(40.0);
+            self.paragraph(0.0, Font::Bold, 13.0, BLUE, &format!("Rule: {}", rule.name));
+            self.tags(0.0, &rule.tags);
+            if !rule.description.is_empty() {
+                self.paragraph(0.0, Font::Regular, 9.0, BLACK, &rule.description);
+            }
+            if let Some(background) = &rule.background {
+                self.background(10.0, background);
+            }
+            for scenario in &rule.scenarios {
+                self.scenario(10.0, scenario);
+            }
+        }
+    }
+
+    fn tags(&mut self, indent: f32, tags: &[String]) {
+        if !tags.is_empty() {
+            let tags = tags.iter().map(|tag| format!("@{tag}")).collect::<Vec<_>>();
+            self.paragraph(indent, Font::Regular, 8.0, BLUE, &tags.join(" "));
+        }
+    }
+
+    fn background(&mut self, indent: f32, background: &BackgroundRenderData) {
+        self.gap(6.0);
+        self.space(30.0);
+        let title = match background.name.as_str() {
+            "" => background.keyword.clone(),
+            name => format!("{}: {name}", background.keyword),
+        };
+        self.paragraph(indent, Font::Bold, 11.0, BLACK, &title);
+        if !background.description.is_empty() {
+            self.paragraph(indent, Font::Regular, 9.0, BLACK, &background.description);
+        }
+        self.steps(indent + 10.0, &background.steps);
+    }
+
+    fn scenario(&mut self, indent: f32, scenario: &ScenarioReportData) {
+        self.gap(6.0);
+        self.space(30.0);
+        match scenario {
+            ScenarioReportData::Scenario(scenario) => {
+                let steps = scenario.all_steps();
+                let state = scenario.state();
+                let mut title = format!("Scenario: {} - {}", scenario.name, state_name(&state));
+                if scenario.flaky {
+                    title.push_str(" (flaky)");
+                }
+                if let Some(duration) = &scenario.duration {
+                    title.push_str(&format!(" ({})", duration.display));
+                }
+                self.paragraph(indent, Font::Bold, 11.0, state_color(&state), &title);
+                self.tags(indent, &scenario.tags);
+                if !scenario.description.is_empty() {
+                    self.paragraph(indent, Font::Regular, 9.0, BLACK, &scenario.description);
+                }
+                self.steps(indent + 10.0, &steps);
+                for attempt in &scenario.attempts {
+                    self.paragraph(
+                        indent + 10.0,
+                        Font::Regular,
+                        8.0,
+                        state_color(&attempt.state),
+                        &format!(
+                            "Attempt {}: {}",
+                            attempt.attempt,
+                            state_name(&attempt.state)
+                        ),
+                    );
+                }
+            }
+            ScenarioReportData::Outline(outline) => {
+                self.paragraph(
+                    indent,
+                    Font::Bold,
+                    11.0,
+                    BLACK,
+                    &format!("Scenario Outline: {}", outline.name),
+                );
+                self.tags(indent, &outline.tags);
+                if !outline.scenario_description.is_empty() {
+                    self.paragraph(
+                        indent,
+                        Font::Regular,
+                        9.0,
+                        BLACK,
+                        &outline.scenario_description,
+                    );
+                }
+                for step in &outline.steps {
+                    let step_indent = if step.continuation { 20.0 } else { 10.0 };
+                    self.paragraph(
+                        indent + step_indent,
+                        Font::Regular,
+                        9.0,
+                        BLACK,
+                        &format!("{} {}", step.keyword, step.step_template),
+                    );
+                }
+                for example in &outline.examples {
+                    self.examples(indent + 10.0, example);
+                }
+            }
+        }
+    }
+
+    fn examples(&mut self, indent: f32, example: &ExampleRenderData) {
+        self.gap(4.0);
+        self.space(30.0);
+        let title = match example.name.as_str() {
+            "" => "Examples".to_string(),
+            name => format!("Examples: {name}"),
+        };
+        self.paragraph(indent, Font::Bold, 10.0, BLACK, &title);
+        self.tags(indent, &example.tags);
+
+        // the outcome gets a column after the values
+        let width =
+            (PAGE_WIDTH - 2.0 * MARGIN - indent - 80.0) / example.headers.len().max(1) as f32;
+        let column = |id: usize| indent + id as f32 * width;
+        let mut header = example
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(id, h)| (column(id), h.clone(), BLACK))
+            .collect::<Vec<_>>();
+        header.push((column(example.headers.len()), "Outcome".to_string(), BLACK));
+        self.row(&header, Font::Bold, 8.0);
+
+        for row in &example.rows {
+            let color = state_color(&row.example_state);
+            let mut cells = row
+                .example
+                .iter()
+                .enumerate()
+                .map(|(id, value)| (column(id), value.clone(), BLACK))
+                .collect::<Vec<_>>();
+            let mut outcome = state_name(&row.example_state).to_string();
+            if row.flaky {
+                outcome.push_str(" (flaky)");
+            }
+            cells.push((column(row.example.len()), outcome, color));
+            self.row(&cells, Font::Regular, 8.0);
+
+            // the steps are only of interest when the row did not pass
+            if row.example_state != StepState::Passed {
+                self.steps(indent + 10.0, &row.all_steps());
+            }
+        }
+    }
+
+    fn steps(&mut self, indent: f32, steps: &[StepRenderData]) {
+        for step in steps {
+            let step_indent = if step.continuation {
+                indent + 10.0
+            } else {
+                indent
+            };
+            let color = state_color(&step.step_state);
+            self.wrapped(
+                step_indent,
+                OUTCOME_WIDTH + 10.0,
+                Font::Regular,
+                9.0,
+                BLACK,
+                &format!("{} {}", step.keyword, step.step_template),
+            );
+            // the outcome is shown on the last line of the step, at the right
+            let state = state_name(&step.step_state);
+            let duration = step
+                .duration
+                .as_ref()
+                .map(|d| format!(" ({})", d.display))
+                .unwrap_or_default();
+            let x = PAGE_WIDTH - MARGIN - OUTCOME_WIDTH;
+            self.text(x, Font::Bold, 8.0, color, &format!("{state}{duration}"));
+
+            if let Some(table) = &step.step_table {
+                for row in table {
+                    self.paragraph(
+                        step_indent + 10.0,
+                        Font::Mono,
+                        8.0,
+                        BLACK,
+                        &format!("| {} |", row.join(" | ")),
+                    );
+                }
+            }
+            if let Some(docstring) = &step.step_docstring {
+                self.paragraph(
+                    step_indent + 10.0,
+                    Font::Mono,
+                    8.0,
+                    BLACK,
+                    &docstring.content,
+                );
+            }
+            if let Some(error) = &step.step_error {
+                self.paragraph(step_indent + 10.0, Font::Mono, 8.0, color, &error.message);
+            }
+        }
+    }
+
+    fn finish(self, title: &str) -> Vec<u8> {
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let tree_id = Ref::new(2);
+        let info_id = Ref::new(3);
+        let fonts = [
+            (Font::Regular, Ref::new(4), "Helvetica"),
+            (Font::Bold, Ref::new(5), "Helvetica-Bold"),
+            (Font::Mono, Ref::new(6), "Courier"),
+        ];
+        let first_page = 7;
+        let page_ids = (0..self.pages.len())
+            .map(|id| Ref::new(first_page + 2 * id as i32))
+            .collect::<Vec<_>>();
+
+        pdf.catalog(catalog_id).pages(tree_id);
+        pdf.pages(tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        pdf.document_info(info_id)
+            .title(TextStr(title))
+            .producer(TextStr("cucumber-reporter"));
+        for (_, id, base_font) in fonts {
+            pdf.type1_font(id)
+                .base_font(Name(base_font.as_bytes()))
+                .encoding_predefined(Name(b"WinAnsiEncoding"));
+        }
+
+        let nr_pages = self.pages.len();
+        for (id, (mut content, page_id)) in self.pages.into_iter().zip(&page_ids).enumerate() {
+            let footer = encode(&format!("{title} - page {} of {nr_pages}", id + 1));
+            content
+                .set_fill_rgb(GREY.0, GREY.1, GREY.2)
+                .begin_text()
+                .set_font(Font::Regular.name(), 7.0)
+                .next_line(MARGIN, MARGIN / 2.0)
+                .show(Str(&footer))
+                .end_text();
+
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(*page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .parent(tree_id)
+                .contents(content_id);
+            let mut resources = page.resources();
+            let mut font_resources = resources.fonts();
+            for (font, font_id, _) in fonts {
+                font_resources.pair(font.name(), font_id);
+            }
+            font_resources.finish();
+            resources.finish();
+            page.finish();
+            pdf.stream(content_id, &content.finish());
+        }
+        pdf.finish()
+    }
+}
+
+/// Splits a line on whitespace into parts of at most `max_chars`,
+/// longer words are cut
+fn wrap(line: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        let mut word = word.to_string();
+        while word.chars().count() > max_chars {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let rest = word.chars().skip(max_chars).collect::<String>();
+            lines.push(word.chars().take(max_chars).collect());
+            word = rest;
+        }
+        if current.chars().count() + word.chars().count() + 1 > max_chars && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+    lines.push(current);
+    lines
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut text = text
+            .chars()
+            .take(max_chars.saturating_sub(3))
+            .collect::<String>();
+        text.push_str("...");
+        text
+    }
+}
@@ -0,0 +1,116 @@
+use handlebars::Handlebars;
+
+use super::{ReporterConfig, Result, index_data};
+use crate::render_types::*;
+
+/// Renders a compact markdown summary of the run, from the same data as the index
+pub(super) fn summary_render(
+    templates: &Handlebars<'_>,
+    config: &ReporterConfig,
+    report: &ReportData,
+) -> Result<String> {
+    let index = index_data(config, report);
+    let stats = &index.features;
+    let sum = |count: fn(&FeatureRenderStatsData) -> usize| stats.iter().map(count).sum();
+
+    let data = SummaryRenderData {
+        title: markdown_text(&index.title),
+        passed: index.status == StepState::Passed,
+        status: index.status.clone(),
+        duration: index.duration.clone(),
+        nr_features: stats.len(),
+        nr_scenarios: sum(|f| f.nr_scenarios),
+        nr_steps: sum(|f| f.nr_steps),
+        nr_errors: sum(|f| f.nr_errors),
+        nr_undefined: sum(|f| f.nr_undefined),
+        nr_ambiguous: sum(|f| f.nr_ambiguous),
+        nr_skipped: sum(|f| f.nr_skipped),
+        nr_not_run: sum(|f| f.nr_not_run),
+        failures: report.features.iter().flat_map(failures).collect(),
+        parse_errors: index
+            .parse_errors
+            .iter()
+            .map(|error| ParseErrorRenderData {
+                path: markdown_text(&error.path),
+                message: markdown_text(&error.message),
+                ..error.clone()
+            })
+            .collect(),
+        features: report
+            .features
+            .iter()
+            .map(|feature| SummaryFeatureRenderData {
+                passed: feature.stats.error.is_none()
+                    && feature.stats.nr_errors == 0
+                    && feature.stats.nr_ambiguous == 0
+                    && feature.stats.nr_background_errors == 0,
+                stats: feature.stats.clone(),
+                scenarios: feature
+                    .scenario_runs()
+                    .into_iter()
+                    .map(|(_, run)| SummaryScenarioRenderData {
+                        name: markdown_text(&run.display_name()),
+                        state: run.state(),
+                        duration: run.duration.cloned(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+    Ok(templates.render("summary.md", &data)?)
+}
+
+/// The scenarios that failed or have an undefined step, with the step causing it
+fn failures(feature: &FeatureReportData) -> Vec<SummaryFailureRenderData> {
+    feature
+        .scenario_runs()
+        .into_iter()
+        .filter_map(|(_, run)| {
+            let step = run.all_steps().into_iter().find(|step| {
+                step.step_state.is_failure() || step.step_state == StepState::Undefined
+            })?;
+            let message = step.step_error.as_ref().map(|error| error.message.clone());
+            Some(SummaryFailureRenderData {
+                feature: markdown_text(&feature.stats.name),
+                scenario: markdown_text(&run.display_name()),
+                step: markdown_text(&format!(
+                    "{} {} ({:?})",
+                    step.keyword, step.step_template, step.step_state
+                )),
+                fence: fence(message.as_deref().unwrap_or_default()),
+                message,
+            })
+        })
+        .collect()
+}
+
+/// A code fence the text can not close, at least three backticks and one more than
+/// the longest run of backticks in it
+fn fence(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(3.max(longest + 1))
+}
+
+/// Text on a single line with the characters markdown would interpret escaped
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+    {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,8 @@
+function filterTag(tag) {
+    document.querySelectorAll('[data-tags]').forEach(function (element) {
+        element.style.display = !tag || element.dataset.tags.split(' ').includes(tag) ? '' : 'none';
+    });
+    document.querySelectorAll('.tag_filter .tag').forEach(function (chip) {
+        chip.classList.toggle('active', chip.dataset.tag === tag);
+    });
+}
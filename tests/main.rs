@@ -1,6 +1,6 @@
 use cucumber::{World, WriterExt, writer::Basic};
 use cucumber_reporter::CucumberReporter;
-use steps::test_steps::ReporterWorld;
+use steps::{hooks, test_steps::ReporterWorld};
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{Layer, fmt::format::{self, Format}, layer::SubscriberExt};
 
@@ -19,6 +19,8 @@ async fn main() {
                 .with(LevelFilter::INFO.and_then(fmt_layer))
         },
     )
+        .before(hooks::before)
+        .after(hooks::after)
         .with_writer(
             Basic::stdout()
                 .summarized()
@@ -0,0 +1,1018 @@
+//! Runs the features once with every report enabled and checks what the reports contain
+use cucumber::{
+    World,
+    cli::{self, Parser},
+};
+use cucumber_reporter::{CucumberReporter, ReportFormat};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use steps::{hooks, test_steps::ReporterWorld};
+
+mod steps;
+
+/// The directory with the reports, the features run for the first test that needs them
+fn reports() -> &'static Path {
+    static REPORTS: OnceLock<PathBuf> = OnceLock::new();
+    REPORTS.get_or_init(|| {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reports");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = |name: &str| dir.join(name).display().to_string();
+        let args = [
+            "outputs".to_string(),
+            "--output-html-path".to_string(),
+            path(""),
+            "--output-json".to_string(),
+            "--output-junit-path".to_string(),
+            path("junit.xml"),
+            "--output-messages-path".to_string(),
+            path("messages.ndjson"),
+            "--output-single-html-path".to_string(),
+            path("single.html"),
+            "--output-markdown-path".to_string(),
+            path("summary.md"),
+            "--output-allure-path".to_string(),
+            path("allure-results"),
+            "--output-ctrf-path".to_string(),
+            path("ctrf.json"),
+            "--output-pdf-path".to_string(),
+            path("report.pdf"),
+        ];
+        run(CucumberReporter::new(), args, "features");
+        dir
+    })
+}
+
+fn run(reporter: CucumberReporter, args: impl IntoIterator<Item = String>, features: &str) {
+    let cli = cli::Opts::<_, _, _, cli::Empty>::parse_from(args);
+    tokio::runtime::Runtime::new()
+        .expect("a tokio runtime")
+        .block_on(
+            ReporterWorld::cucumber()
+                .before(hooks::before)
+                .after(hooks::after)
+                .with_writer(reporter)
+                .with_cli(cli)
+                .run(features),
+        );
+}
+
+fn read(name: &str) -> String {
+    let path = reports().join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+fn json(name: &str) -> Value {
+    serde_json::from_str(&read(name)).unwrap_or_else(|e| panic!("{name}: {e}"))
+}
+
+fn named<'a>(values: &'a Value, name: &str) -> &'a Value {
+    values
+        .as_array()
+        .and_then(|values| values.iter().find(|value| value["name"] == name))
+        .unwrap_or_else(|| panic!("'{name}' not found"))
+}
+
+fn states(steps: &Value) -> Vec<&str> {
+    steps
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .filter_map(|s| s["step_state"].as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn nr_feature_files() -> usize {
+    std::fs::read_dir("features")
+        .expect("the features directory")
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .is_ok_and(|e| e.path().extension().is_some_and(|ext| ext == "feature"))
+        })
+        .count()
+}
+
+/// Scenarios and example rows that ran, the way every report should count them
+fn nr_scenario_runs() -> usize {
+    let report = json("report.json");
+    report["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["nr_scenarios"].as_u64().unwrap() as usize)
+        .sum()
+}
+
+#[test]
+fn json_report_has_every_feature() {
+    let report = json("report.json");
+    assert_eq!(report["status"], "Failed");
+    let nr_parse_errors = report["parse_errors"].as_array().unwrap().len();
+    assert_eq!(
+        report["features"].as_array().unwrap().len(),
+        nr_feature_files() - nr_parse_errors
+    );
+}
+
+#[test]
+fn failed_steps_have_their_message_and_payload() {
+    let report = json("report.json");
+    let feature = named(&report["features"], "step states");
+    let scenario = named(&feature["scenarios"], "Scenario with a failed step");
+    let error = &scenario["steps"][1]["step_error"];
+    assert_eq!(
+        error["message"],
+        "Step panicked. Captured output: expected error"
+    );
+    assert_eq!(error["payload"], "expected error");
+    assert!(scenario["steps"][0]["step_error"].is_null());
+
+    let page = read(feature["link"].as_str().unwrap());
+    assert!(page.contains("expected error"));
+}
+
+#[test]
+fn json_report_has_the_durations() {
+    let report = json("report.json");
+    let run = &report["duration"];
+    assert!(run["start"].is_u64());
+    let run_millis = run["millis"].as_u64().unwrap();
+    for feature in report["features"].as_array().unwrap() {
+        let millis = feature["duration"]["millis"].as_u64().unwrap();
+        assert!(millis <= run_millis, "{}", feature["name"]);
+    }
+
+    let feature = named(&report["features"], "step states");
+    let scenario = named(&feature["scenarios"], "Scenario with a failed step");
+    assert!(scenario["duration"]["display"].is_string());
+    assert!(scenario["steps"][0]["duration"]["start"].is_u64());
+    // a step after the failed one never ran
+    assert!(scenario["steps"][2]["duration"].is_null());
+}
+
+#[test]
+fn junit_has_a_testcase_per_scenario_run() {
+    let junit = read("junit.xml");
+    let junit = roxmltree::Document::parse(&junit).expect("valid xml");
+    let cases = junit
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .collect::<Vec<_>>();
+    assert_eq!(cases.len(), nr_scenario_runs());
+    let failures = cases
+        .iter()
+        .filter(|case| case.children().any(|c| c.has_tag_name("failure")))
+        .count();
+    assert_eq!(
+        junit.root_element().attribute("failures"),
+        Some(failures.to_string().as_str())
+    );
+    let suites = junit
+        .descendants()
+        .filter(|node| node.has_tag_name("testsuite"))
+        .count();
+    assert_eq!(
+        suites,
+        json("report.json")["features"].as_array().unwrap().len()
+    );
+}
+
+#[test]
+fn messages_are_one_envelope_per_line() {
+    let messages = read("messages.ndjson");
+    let envelopes = messages
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).expect("json envelope"))
+        .collect::<Vec<_>>();
+    let count = |kind: &str| envelopes.iter().filter(|e| e.get(kind).is_some()).count();
+    let nr_scenario_runs = nr_scenario_runs();
+    assert_eq!(count("meta"), 1);
+    assert!(envelopes[0].get("meta").is_some(), "meta comes first");
+    assert_eq!(count("testRunStarted"), 1);
+    assert_eq!(count("pickle"), nr_scenario_runs);
+    assert_eq!(count("testCase"), nr_scenario_runs);
+    assert_eq!(count("testCaseStarted"), count("testCaseFinished"));
+    assert_eq!(count("testStepStarted"), count("testStepFinished"));
+    let finished = envelopes.last().expect("envelopes");
+    assert_eq!(finished["testRunFinished"]["success"], false);
+}
+
+fn envelopes() -> Vec<Value> {
+    read("messages.ndjson")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json envelope"))
+        .collect()
+}
+
+#[test]
+fn messages_have_the_sources_documents_and_pickles_before_the_test_run() {
+    let envelopes = envelopes();
+    let kind = |envelope: &Value| {
+        envelope
+            .as_object()
+            .and_then(|e| e.keys().next().cloned())
+            .unwrap_or_default()
+    };
+    let run_started = envelopes
+        .iter()
+        .position(|e| e.get("testRunStarted").is_some())
+        .expect("the test run");
+    let before_the_run = ["source", "gherkinDocument", "pickle", "parseError"];
+    for envelope in &envelopes[run_started..] {
+        assert!(
+            !before_the_run.contains(&kind(envelope).as_str()),
+            "{envelope}"
+        );
+    }
+
+    let documents = envelopes
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.get("gherkinDocument").is_some());
+    for (id, document) in documents {
+        let uri = &document["gherkinDocument"]["uri"];
+        let source = &envelopes[id - 1]["source"];
+        assert_eq!(&source["uri"], uri, "the source comes before its document");
+        let path = uri.as_str().unwrap();
+        assert_eq!(source["data"], std::fs::read_to_string(path).unwrap());
+    }
+}
+
+#[test]
+fn messages_match_the_messages_schema() {
+    let schema: Value =
+        serde_json::from_str(include_str!("messages.schema.json")).expect("the schema");
+    let validator = jsonschema::validator_for(&schema).expect("a valid schema");
+    for envelope in envelopes() {
+        if let Err(error) = validator.validate(&envelope) {
+            panic!("{error} at {}: {envelope}", error.instance_path);
+        }
+    }
+}
+
+#[test]
+fn messages_have_the_language_and_table_rows_of_the_file() {
+    let envelopes = envelopes();
+    let document = |name: &str| {
+        envelopes
+            .iter()
+            .map(|e| &e["gherkinDocument"]["feature"])
+            .find(|feature| feature["name"] == name)
+            .unwrap_or_else(|| panic!("'{name}' not found"))
+    };
+    assert_eq!(document("in het Nederlands")["language"], "nl");
+    assert_eq!(document("outline templates")["language"], "en");
+    let pickle = envelopes
+        .iter()
+        .map(|e| &e["pickle"])
+        .find(|pickle| pickle["name"] == "Een scenario")
+        .expect("the dutch pickle");
+    assert_eq!(pickle["language"], "nl");
+
+    let scenario = document("outline")["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|child| &child["scenario"])
+        .find(|scenario| scenario["name"] == "Scenario with skipped extra new line")
+        .expect("the outline");
+    let examples = &scenario["examples"][0];
+    let lines = std::iter::once(&examples["tableHeader"])
+        .chain(examples["tableBody"].as_array().unwrap())
+        .map(|row| row["location"]["line"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [28, 30, 32]);
+}
+
+#[test]
+fn messages_have_the_parse_error_and_the_hooks() {
+    let envelopes = envelopes();
+    let parse_errors = envelopes
+        .iter()
+        .filter_map(|e| e.get("parseError"))
+        .collect::<Vec<_>>();
+    assert_eq!(parse_errors.len(), 1);
+    let source = &parse_errors[0]["source"];
+    assert_eq!(source["uri"], "features/feature-syntaxt-error.feature");
+    assert_eq!(source["location"]["line"], 6);
+
+    let hooks = envelopes
+        .iter()
+        .filter_map(|e| e.get("hook"))
+        .map(|hook| hook["type"].as_str().unwrap())
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        hooks,
+        HashSet::from(["BEFORE_TEST_CASE", "AFTER_TEST_CASE"])
+    );
+    let failed_hooks = envelopes
+        .iter()
+        .filter_map(|e| e["testStepFinished"]["testStepResult"]["message"].as_str())
+        .filter(|message| message.contains("hook failed"))
+        .count();
+    assert!(failed_hooks >= 2, "{failed_hooks} failed hooks");
+    let hook_steps = envelopes
+        .iter()
+        .filter_map(|e| e["testCase"]["testSteps"].as_array())
+        .flatten()
+        .filter(|step| step.get("hookId").is_some())
+        .count();
+    let test_cases = envelopes
+        .iter()
+        .filter(|e| e.get("testCase").is_some())
+        .count();
+    assert_eq!(hook_steps, 2 * test_cases);
+}
+
+#[test]
+fn readme_lists_every_template() {
+    let readme = std::fs::read_to_string("README.md").expect("the readme");
+    for entry in std::fs::read_dir("templates").expect("the templates directory") {
+        let name = entry.unwrap().file_name();
+        let name = name.to_string_lossy();
+        assert!(readme.contains(&format!("- `{name}`")), "{name}");
+    }
+}
+
+#[test]
+fn templates_in_the_template_dir_replace_the_embedded_ones() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("template-dir");
+    let _ = std::fs::remove_dir_all(&dir);
+    let templates = dir.join("templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    std::fs::write(templates.join("summary.md"), "{{title}}: {{status}}").unwrap();
+    let path = |name: &str| dir.join(name).display().to_string();
+    let args = [
+        "outputs".to_string(),
+        "--output-html-path".to_string(),
+        path(""),
+        "--template-dir".to_string(),
+        path("templates"),
+        "--output-markdown-path".to_string(),
+        path("summary.md"),
+    ];
+    run(
+        CucumberReporter::new(),
+        args,
+        "features/feature-simple.feature",
+    );
+
+    let summary = std::fs::read_to_string(dir.join("summary.md")).unwrap();
+    assert_eq!(summary, "Cucumber report: Failed");
+    let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+    assert!(
+        index.contains("features_feature-simple.html"),
+        "embedded index"
+    );
+}
+
+#[test]
+fn commandline_paths_add_their_report_to_the_builder_formats() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("builder-formats");
+    let _ = std::fs::remove_dir_all(&dir);
+    let reporter = CucumberReporter::builder()
+        .formats([ReportFormat::Junit])
+        .build();
+    let args = [
+        "outputs".to_string(),
+        "--output-html-path".to_string(),
+        dir.display().to_string(),
+    ];
+    run(reporter, args, "features/feature-simple.feature");
+
+    assert!(dir.join("index.html").is_file());
+    assert!(dir.join("junit.xml").is_file());
+}
+
+#[test]
+fn doc_strings_have_their_content_type_and_indentation() {
+    let report = json("report.json");
+    let feature = named(&report["features"], "doc strings");
+    let json_body = named(&feature["scenarios"], "A step with a json doc string");
+    let docstring = &json_body["steps"][0]["step_docstring"];
+    assert_eq!(docstring["content_type"], "json");
+    assert!(
+        docstring["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("{\n    \"name\": \"cucumber\"")
+    );
+    assert!(json_body["steps"][1]["step_docstring"].is_null());
+
+    let plain = named(&feature["scenarios"], "A step with a plain doc string");
+    let docstring = &plain["steps"][0]["step_docstring"];
+    assert!(docstring["content_type"].is_null());
+    assert_eq!(
+        docstring["content"],
+        "Some text\n  that keeps its indentation"
+    );
+}
+
+#[test]
+fn markdown_descriptions_are_rendered_and_sanitized() {
+    let page = "features_feature-markdown-description.html";
+    assert!(
+        !read(page).contains("<strong>markdown</strong>"),
+        "plain by default"
+    );
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("markdown-descriptions");
+    let _ = std::fs::remove_dir_all(&dir);
+    let args = [
+        "outputs".to_string(),
+        "--output-html-path".to_string(),
+        dir.display().to_string(),
+        "--markdown-descriptions".to_string(),
+    ];
+    let features = "features/feature-markdown-description.feature";
+    run(CucumberReporter::new(), args, features);
+
+    let html = std::fs::read_to_string(dir.join(page)).unwrap();
+    assert!(html.contains("<strong>markdown</strong>"));
+    assert!(html.contains("<li>lists are rendered as lists</li>"));
+    assert!(html.contains("<a href=\"https://cucumber.io\""));
+    assert!(!html.contains("alert(\"hostile\")"));
+}
+
+#[test]
+fn scenarios_inherit_the_tags_of_their_feature_and_rule() {
+    let report = json("report.json");
+    // another feature is named tags as well
+    let feature = report["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["link"] == "features_feature-with-tags.html")
+        .expect("the tags feature");
+    assert_eq!(feature["tags"], serde_json::json!(["tagged"]));
+    let regression = named(&feature["scenarios"], "A regression test");
+    assert_eq!(
+        regression["all_tags"],
+        serde_json::json!(["regression", "tagged", "team-a"])
+    );
+    let rule = named(&feature["rules"], "a rule with tags");
+    let inheriting = named(&rule["scenarios"], "Scenario inheriting the rule tags");
+    assert_eq!(
+        inheriting["all_tags"],
+        serde_json::json!(["tagged", "team-b"])
+    );
+
+    // the example rows with a tag count for that tag
+    let tag = named(&report["tags"], "regression");
+    assert_eq!(tag["nr_scenarios"], 2);
+    assert_eq!(tag["nr_failed"], 2);
+    assert!(read("index.html").contains("team-b"));
+}
+
+#[test]
+fn background_steps_have_their_own_outcome() {
+    let report = json("report.json");
+    let feature = named(&report["features"], "background");
+    assert_eq!(feature["background"]["name"], "common facts");
+    assert_eq!(feature["nr_background_errors"], 1);
+
+    let scenario = named(&feature["scenarios"], "Scenario after the background");
+    assert_eq!(states(&scenario["background"]), ["Passed"]);
+    assert_eq!(states(&scenario["steps"]), ["Passed", "Passed"]);
+
+    // the background of the rule runs after the one of the feature
+    let rule = named(&feature["rules"], "a rule with a failing background");
+    let not_run = named(&rule["scenarios"], "Scenario not run due to the background");
+    assert_eq!(states(&not_run["background"]), ["Passed", "Failed"]);
+    assert_eq!(states(&not_run["steps"]), ["Skipped", "Skipped"]);
+}
+
+#[test]
+fn json_report_has_the_hooks() {
+    let report = json("report.json");
+    let hooks = named(&report["features"], "hooks");
+    assert_eq!(hooks["nr_errors"], 3);
+
+    let passing = named(&hooks["scenarios"], "Scenario with passing hooks");
+    assert_eq!(states(&passing["before_hooks"]), ["Passed"]);
+    assert_eq!(states(&passing["steps"]), ["Passed"]);
+    assert_eq!(states(&passing["after_hooks"]), ["Passed"]);
+
+    let before = named(&hooks["scenarios"], "Scenario with a failing before hook");
+    assert_eq!(states(&before["before_hooks"]), ["Failed"]);
+    assert_eq!(
+        before["before_hooks"][0]["step_error"]["payload"],
+        "before hook failed"
+    );
+    assert_eq!(states(&before["steps"]), ["Skipped"]);
+
+    let after = named(&hooks["scenarios"], "Scenario with a failing after hook");
+    assert_eq!(states(&after["steps"]), ["Passed"]);
+    assert_eq!(states(&after["after_hooks"]), ["Failed"]);
+}
+
+#[test]
+fn json_report_has_the_retries() {
+    let report = json("report.json");
+    let retries = named(&report["features"], "retries");
+    assert_eq!(retries["nr_flaky"], 1);
+
+    let flaky = named(&retries["scenarios"], "Scenario passing after a retry");
+    assert_eq!(flaky["flaky"], true);
+    let attempts = flaky["attempts"].as_array().unwrap();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[0]["state"], "Failed");
+    assert_eq!(attempts[1]["state"], "Passed");
+    assert_eq!(states(&flaky["steps"]), ["Passed", "Passed"]);
+
+    let failing = named(&retries["scenarios"], "Scenario failing every attempt");
+    assert_eq!(failing["flaky"], false);
+    assert_eq!(failing["attempts"].as_array().unwrap().len(), 2);
+    assert_eq!(states(&failing["steps"]), ["Passed", "Failed"]);
+
+    let first_time = named(&retries["scenarios"], "Scenario passing the first time");
+    assert_eq!(first_time["attempts"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn json_report_has_the_parse_error() {
+    let report = json("report.json");
+    let parse_errors = report["parse_errors"].as_array().unwrap();
+    assert_eq!(parse_errors.len(), 1);
+    assert_eq!(
+        parse_errors[0]["path"],
+        "features/feature-syntaxt-error.feature"
+    );
+    assert_eq!(parse_errors[0]["line"], 6);
+    assert!(read("index.html").contains("feature-syntaxt-error.feature"));
+}
+
+#[test]
+fn steps_have_their_own_state() {
+    let report = json("report.json");
+    let feature = named(&report["features"], "step states");
+    assert_eq!(feature["nr_undefined"], 1);
+    assert_eq!(feature["nr_ambiguous"], 1);
+
+    let undefined = named(&feature["scenarios"], "Scenario with an undefined step");
+    assert_eq!(
+        states(&undefined["steps"]),
+        ["Passed", "Undefined", "Skipped"]
+    );
+    let ambiguous = named(&feature["scenarios"], "Scenario with an ambiguous step");
+    assert_eq!(states(&ambiguous["steps"]), ["Ambiguous", "Skipped"]);
+}
+
+#[test]
+fn html_pages_exist_for_every_link_of_the_index() {
+    let index = read("index.html");
+    let report = json("report.json");
+    for feature in report["features"].as_array().unwrap() {
+        let link = feature["link"].as_str().unwrap();
+        let slug = link.strip_suffix(".html").unwrap();
+        assert!(
+            slug.chars()
+                .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-')),
+            "{link} is not a slug"
+        );
+        assert!(index.contains(link), "{link} not in the index");
+        assert!(reports().join(link).is_file(), "{link} not written");
+    }
+}
+
+#[test]
+fn features_with_the_same_name_are_sorted_by_their_page() {
+    let report = json("report.json");
+    let features = report["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["name"].as_str().unwrap(), f["link"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    let mut sorted = features.clone();
+    sorted.sort();
+    assert_eq!(features, sorted);
+    assert!(features.windows(2).any(|pair| pair[0].0 == pair[1].0));
+}
+
+#[test]
+fn broken_templates_are_replaced_by_the_embedded_ones() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("broken-templates");
+    let _ = std::fs::remove_dir_all(&dir);
+    let templates = dir.join("templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    for name in ["index.html", "junit.xml", "summary.md"] {
+        std::fs::write(templates.join(name), "{{#if broken}} never closed").unwrap();
+    }
+    // a misspelled name is listed as a problem, it replaces nothing
+    std::fs::write(templates.join("feature.htm"), "misspelled").unwrap();
+    let reporter = CucumberReporter::builder()
+        .output_dir(&dir)
+        .template_dir(&templates)
+        .junit_path(dir.join("junit.xml"))
+        .markdown_path(dir.join("summary.md"))
+        .ctrf_path(dir.join("ctrf.json"))
+        .build();
+    // only a feature without a flaky step, the steps count their calls
+    let pdf = dir.join("report.pdf");
+    let args = [
+        "outputs".to_string(),
+        "--output-pdf-path".to_string(),
+        pdf.display().to_string(),
+    ];
+    run(reporter, args, "features/feature-simple.feature");
+
+    let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+    assert!(index.contains("features_feature-simple.html"));
+    let junit = std::fs::read_to_string(dir.join("junit.xml")).unwrap();
+    roxmltree::Document::parse(&junit).expect("junit xml");
+    let summary = std::fs::read_to_string(dir.join("summary.md")).unwrap();
+    assert!(!summary.contains("never closed"));
+    let page = std::fs::read_to_string(dir.join("features_feature-simple.html")).unwrap();
+    assert!(!page.contains("misspelled"));
+    assert!(dir.join("ctrf.json").is_file());
+    assert!(pdf.is_file(), "a path on the commandline adds its report");
+}
+
+#[test]
+fn example_rows_with_the_same_values_have_their_own_results() {
+    let report = json("report.json");
+    let hooks = named(&report["features"], "hooks");
+    let outline = named(
+        &hooks["scenarios"],
+        "Outline with a failing before hook for one of the same rows",
+    );
+    let passing = &outline["examples"][0]["rows"][0];
+    let failing = &outline["examples"][1]["rows"][0];
+    assert_eq!(passing["example"], failing["example"]);
+
+    assert_eq!(passing["example_state"], "Passed");
+    assert_eq!(states(&passing["steps"]), ["Passed"]);
+    assert!(passing["steps"][0]["duration"].is_object());
+
+    assert_eq!(failing["example_state"], "Failed");
+    assert_eq!(states(&failing["before_hooks"]), ["Failed"]);
+    assert_eq!(states(&failing["steps"]), ["Skipped"]);
+    assert!(failing["steps"][0]["duration"].is_null());
+}
+
+#[test]
+fn json_report_has_the_outline_rows() {
+    let report = json("report.json");
+    let outline = named(&report["features"], "outline");
+    let outline = named(&outline["scenarios"], "Scenario Outline name <test>");
+    assert_eq!(outline["type"], "Outline");
+    let examples = &outline["examples"][0];
+    assert_eq!(
+        examples["headers"],
+        serde_json::json!(["Header1", "Header2", "test"])
+    );
+    let rows = examples["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[1]["example"][0], "Value 2");
+    assert_eq!(rows[1]["example_state"], "Failed");
+    for row in [&rows[0], &rows[2], &rows[3]] {
+        assert_eq!(row["example_state"], "Passed");
+    }
+    assert_eq!(states(&rows[1]["steps"]), ["Failed", "Skipped"]);
+}
+
+fn templates(steps: &Value) -> Vec<&str> {
+    steps
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .filter_map(|s| s["step_template"].as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn outlines_are_reported_as_written() {
+    let report = json("report.json");
+    let outline = named(&report["features"], "outline");
+    let outline = named(&outline["scenarios"], "Scenario Outline name <test>");
+    assert_eq!(
+        templates(&outline["steps"]),
+        ["a fact with '<Header1>'", "a fact with '<Header2>'"]
+    );
+
+    // without different values per row the placeholders can not be told apart
+    let feature = named(&report["features"], "outline templates");
+    let single_row = named(&feature["scenarios"], "Single row outline");
+    assert_eq!(templates(&single_row["steps"]), ["a fact with 'Value 1'"]);
+
+    let identical = named(&feature["scenarios"], "Outline with identical columns");
+    assert_eq!(
+        identical["scenario_description"],
+        "The columns have the same values,\nthe steps use them in another order"
+    );
+    assert_eq!(
+        identical["examples"][0]["rows"].as_array().unwrap().len(),
+        2
+    );
+}
+
+#[test]
+fn outlines_have_their_own_line_in_the_messages() {
+    let messages = read("messages.ndjson");
+    let document = messages
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|e| e["gherkinDocument"]["feature"]["name"] == "outline templates")
+        .expect("the gherkin document");
+    let children = document["gherkinDocument"]["feature"]["children"]
+        .as_array()
+        .unwrap();
+    let lines = children
+        .iter()
+        .map(|child| child["scenario"]["location"]["line"].as_u64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, [5, 12]);
+}
+
+fn continuations(steps: &Value) -> Vec<bool> {
+    steps
+        .as_array()
+        .map(|steps| {
+            steps
+                .iter()
+                .filter_map(|s| s["continuation"].as_bool())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn and_and_but_steps_are_continuations() {
+    let report = json("report.json");
+    let dutch = named(&report["features"], "in het Nederlands");
+    let scenario = named(&dutch["scenarios"], "Een scenario");
+    assert_eq!(
+        continuations(&scenario["steps"]),
+        [false, true, false, false]
+    );
+
+    // a repeated keyword starts again
+    let outline = named(&report["features"], "outline");
+    let outline = named(&outline["scenarios"], "Scenario Outline name <test>");
+    assert_eq!(continuations(&outline["steps"]), [false, false]);
+
+    // the first step of a scenario does not continue its background,
+    // an attempt has the hooks, the background and the steps
+    let retries = named(&report["features"], "retries");
+    let flaky = named(&retries["scenarios"], "Scenario passing after a retry");
+    assert_eq!(
+        continuations(&flaky["attempts"][0]["steps"]),
+        [false, false, false, true, false]
+    );
+}
+
+#[test]
+fn pdf_has_a_page_per_feature() {
+    let pdf = lopdf::Document::load(reports().join("report.pdf")).expect("a valid pdf");
+    let report = json("report.json");
+    let nr_features = report["features"].as_array().unwrap().len();
+    assert!(pdf.get_pages().len() > nr_features);
+}
+
+/// A text drawn on a pdf page, in the order of the pages
+struct PdfText {
+    x: f32,
+    size: f32,
+    text: Vec<u8>,
+}
+
+fn pdf_texts() -> Vec<PdfText> {
+    let pdf = lopdf::Document::load(reports().join("report.pdf")).expect("a valid pdf");
+    let mut texts = Vec::new();
+    for page in pdf.get_pages().into_values() {
+        let content = pdf.get_page_content(page).expect("page content");
+        let content = lopdf::content::Content::decode(&content).expect("page operations");
+        let (mut size, mut x) = (0.0, 0.0);
+        for operation in content.operations {
+            let operand = |id: usize| operation.operands[id].as_float().unwrap();
+            match operation.operator.as_str() {
+                "Tf" => size = operand(1),
+                "Td" => x = operand(0),
+                "Tj" => texts.push(PdfText {
+                    x,
+                    size,
+                    text: operation.operands[0].as_str().unwrap().to_vec(),
+                }),
+                _ => {}
+            }
+        }
+    }
+    texts
+}
+
+#[test]
+fn pdf_steps_leave_room_for_their_outcome() {
+    let texts = pdf_texts();
+    // the outcome of a step is at the right of its last line
+    let outcome_x = 595.0 - 50.0 - 90.0;
+    let first = texts
+        .iter()
+        .position(|t| {
+            t.text
+                .starts_with(b"Given a fact with 'a value that is long")
+        })
+        .expect("the long step");
+    let lines = texts[first..]
+        .iter()
+        .take_while(|t| t.x != outcome_x)
+        .collect::<Vec<_>>();
+    assert!(lines.len() > 1, "the long step is wrapped");
+    for line in lines {
+        // the lines are wrapped on an average character width of half the font size
+        let end = line.x + line.text.len() as f32 * line.size * 0.5;
+        assert!(
+            end < outcome_x,
+            "'{}' runs into the outcome",
+            String::from_utf8_lossy(&line.text)
+        );
+    }
+}
+
+#[test]
+fn pdf_text_is_win_ansi_encoded() {
+    let text = pdf_texts()
+        .into_iter()
+        .flat_map(|t| t.text)
+        .collect::<Vec<_>>();
+    let contains = |part: &[u8]| text.windows(part.len()).any(|window| window == part);
+    assert!(contains(b"with \x93typographic\x94 quotes, a \x80 sign"));
+    assert!(
+        contains(b"some ??????"),
+        "characters without a code are a ?"
+    );
+}
+
+#[test]
+fn single_html_has_a_section_per_feature() {
+    let single = read("single.html");
+    let report = json("report.json");
+    for feature in report["features"].as_array().unwrap() {
+        let anchor = feature["link"].as_str().unwrap().trim_end_matches(".html");
+        assert!(single.contains(&format!("id=\"{anchor}\"")), "{anchor}");
+        assert!(single.contains(&format!("#{anchor}")), "{anchor}");
+    }
+}
+
+#[test]
+fn every_page_has_the_tag_filter_once() {
+    let report = json("report.json");
+    let links = report["features"].as_array().unwrap().iter();
+    let pages = links.map(|feature| feature["link"].as_str().unwrap());
+    for page in ["index.html", "single.html"].into_iter().chain(pages) {
+        let html = read(page);
+        assert_eq!(html.matches("function filterTag").count(), 1, "{page}");
+    }
+}
+
+#[test]
+fn markdown_summary_has_the_totals_and_failures() {
+    let summary = read("summary.md");
+    let report = json("report.json");
+    let nr_features = report["features"].as_array().unwrap().len();
+    assert!(summary.starts_with("## Cucumber report"));
+    assert!(summary.contains(":x: **Failed**"));
+    assert!(summary.contains(&format!("| {nr_features} |")));
+    assert!(summary.contains("### Failures"));
+    assert!(summary.contains("Hook panicked. Captured output: before hook failed"));
+    assert!(summary.contains("| Ambiguous |"));
+    assert!(summary.contains(
+        "````\nStep panicked. Captured output: code block failure\n```\nlet answer = 42;\n```\n````"
+    ));
+}
+
+#[test]
+fn allure_has_a_result_per_scenario_run() {
+    let report = json("report.json");
+    let features = report["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect::<HashSet<_>>();
+    let results = std::fs::read_dir(reports().join("allure-results"))
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with("-result.json")
+        })
+        .map(|entry| {
+            serde_json::from_str::<Value>(&std::fs::read_to_string(entry.path()).unwrap()).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), nr_scenario_runs());
+    for result in &results {
+        let feature = named(&result["labels"], "feature");
+        assert!(features.contains(feature["value"].as_str().unwrap()));
+        assert_eq!(result["stage"], "finished");
+    }
+}
+
+/// A scenario run and its status, the way every report should list it
+fn ctrf_tests() -> Vec<(String, String)> {
+    let ctrf = json("ctrf.json");
+    ctrf["results"]["tests"]
+        .as_array()
+        .expect("ctrf tests")
+        .iter()
+        .map(|test| {
+            (
+                test["name"].as_str().unwrap_or_default().to_string(),
+                test["status"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn reports_have_the_same_scenario_runs() {
+    let tests = ctrf_tests();
+    let report = json("report.json");
+    let nr_scenarios = report["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["nr_scenarios"].as_u64().unwrap() as usize)
+        .sum::<usize>();
+    assert_eq!(tests.len(), nr_scenarios);
+
+    let junit = read("junit.xml");
+    let junit = roxmltree::Document::parse(&junit).expect("valid xml");
+    let cases = junit
+        .descendants()
+        .filter(|node| node.has_tag_name("testcase"))
+        .collect::<Vec<_>>();
+    assert_eq!(cases.len(), tests.len());
+    let failed = tests
+        .iter()
+        .filter(|(_, status)| status == "failed")
+        .count();
+    let failures = cases
+        .iter()
+        .filter(|case| case.children().any(|c| c.has_tag_name("failure")))
+        .count();
+    assert_eq!(failures, failed);
+    assert_eq!(
+        junit.root_element().attribute("failures"),
+        Some(failed.to_string().as_str())
+    );
+
+    let allure = std::fs::read_dir(reports().join("allure-results"))
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .ends_with("-result.json")
+        })
+        .map(|entry| {
+            let result: Value =
+                serde_json::from_str(&std::fs::read_to_string(entry.path()).unwrap()).unwrap();
+            result["status"].as_str().unwrap().to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(allure.len(), tests.len());
+    assert_eq!(
+        allure.iter().filter(|status| *status == "passed").count(),
+        tests
+            .iter()
+            .filter(|(_, status)| status == "passed")
+            .count()
+    );
+}
+
+#[test]
+fn ctrf_summary_counts_the_tests() {
+    let ctrf = json("ctrf.json");
+    let summary = &ctrf["results"]["summary"];
+    let tests = ctrf_tests();
+    assert_eq!(summary["tests"], tests.len());
+    for status in ["passed", "failed", "pending", "skipped", "other"] {
+        assert_eq!(
+            summary[status],
+            tests.iter().filter(|(_, s)| s == status).count(),
+            "{status}"
+        );
+    }
+    assert_eq!(summary["extra"]["nr_parse_errors"], 1);
+    assert_eq!(summary["extra"]["nr_flaky"], 1);
+    assert!(tests.contains(&(
+        "Scenario Outline name <test> (Value 2, Value 2, 2)".into(),
+        "failed".into()
+    )));
+    assert!(tests.contains(&("Scenario with an undefined step".into(), "pending".into())));
+}
@@ -0,0 +1,37 @@
+use std::{future::Future, pin::Pin};
+
+use cucumber::event::ScenarioFinished;
+use gherkin::{Feature, Rule, Scenario};
+
+use super::test_steps::ReporterWorld;
+
+type HookFuture<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// Fails for the scenarios tagged `@failing_before_hook`
+pub fn before<'a>(
+    _feature: &'a Feature,
+    _rule: Option<&'a Rule>,
+    scenario: &'a Scenario,
+    _world: &'a mut ReporterWorld,
+) -> HookFuture<'a> {
+    Box::pin(async move {
+        if scenario.tags.iter().any(|tag| tag == "failing_before_hook") {
+            panic!("before hook failed");
+        }
+    })
+}
+
+/// Fails for the scenarios tagged `@failing_after_hook`
+pub fn after<'a>(
+    _feature: &'a Feature,
+    _rule: Option<&'a Rule>,
+    scenario: &'a Scenario,
+    _finished: &'a ScenarioFinished,
+    _world: Option<&'a mut ReporterWorld>,
+) -> HookFuture<'a> {
+    Box::pin(async move {
+        if scenario.tags.iter().any(|tag| tag == "failing_after_hook") {
+            panic!("after hook failed");
+        }
+    })
+}
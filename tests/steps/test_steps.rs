@@ -1,4 +1,5 @@
 use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use cucumber::{World, given, then, when};
 use gherkin::Step;
@@ -21,6 +22,15 @@ pub async fn given_some_fact(_world: &mut ReporterWorld,step:&Step) -> Result<()
     }
 }
 
+#[given(expr = "a request body")]
+pub async fn given_a_request_body(_world: &mut ReporterWorld, step: &Step) -> Result<()> {
+    if step.docstring().is_some() {
+        Ok(())
+    } else {
+        Err("no doc string".into())
+    }
+}
+
 #[given(expr = "a fact with {string}")]
 pub async fn given_a_fact_with(_world: &mut ReporterWorld, value: String) -> Result<()> {
     if value == "Value 2" {
@@ -41,3 +51,34 @@ pub async fn then_result_is(_world: &mut ReporterWorld, outcome: String) -> Resu
         Err("expected error".into())
     }
 }
+
+/// Fails with the escape codes of a terminal color, which XML does not allow
+#[then(expr = "the output is colored")]
+pub async fn then_output_is_colored(_world: &mut ReporterWorld) -> Result<()> {
+    Err("\u{1b}[31mcolored failure\u{1b}[0m".into())
+}
+
+/// Fails with a markdown code block, which must not end the fence around it
+#[then(expr = "the output has a code block")]
+pub async fn then_output_has_code_block(_world: &mut ReporterWorld) -> Result<()> {
+    Err("code block failure\n```\nlet answer = 42;\n```".into())
+}
+
+static FLAKY_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Fails the first time only, to test retries
+#[given(expr = "a flaky fact")]
+pub async fn given_a_flaky_fact(_world: &mut ReporterWorld) -> Result<()> {
+    if FLAKY_CALLS.fetch_add(1, Ordering::SeqCst) == 0 {
+        Err("flaky failure".into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Both match "an ambiguous fact", to test ambiguous steps
+#[given(expr = "an ambiguous fact")]
+pub async fn given_an_ambiguous_fact(_world: &mut ReporterWorld) {}
+
+#[given(regex = r"^an ambiguous (.*)$")]
+pub async fn given_an_ambiguous_thing(_world: &mut ReporterWorld, _thing: String) {}